@@ -0,0 +1,499 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::{PermissionsExt, symlink};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::Ordering;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde_derive::{Serialize, Deserialize};
+use crate::config_helpers::get_cache_home;
+use crate::fs_helpers::{FileOpError, move_into_place, remove_path, unique_name};
+use crate::job_helpers::{JobOutcome, JobProgress, measure};
+use crate::journal_helpers::JournalOp;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+const SYMLINK_MODE: u32 = 0o120000;
+const TYPE_MASK: u32 = 0o170000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ArchiveFormat {//enum representing the archive formats we can read and write
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+pub struct ArchivePrompt {//struct representation of the compress and extract-to prompt, target is what the user typed
+    pub paths: Vec<PathBuf>,
+    pub compress: bool,
+    pub target: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ArchiveMount {//an archive being browsed, unpacked into a mirror directory that stands in for it
+    pub archive: PathBuf,
+    pub shown: PathBuf,//where the archive sits as far as the path bar is concerned, this can be inside another archive
+    pub mirror: PathBuf,
+}
+
+pub fn format_for_name(name: &str) -> Option<ArchiveFormat> {//pick a format by extension
+    let name = name.to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveFormat::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveFormat::TarGz)
+    } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+        Some(ArchiveFormat::TarZst)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveFormat::Tar)
+    } else {
+        None
+    }
+}
+pub fn format_for_mime(mime: &str, name: &str) -> Option<ArchiveFormat> {//recognise an archive by the mimetype xdg-mime gave us, compressed tarballs fall back on the extension
+    match mime.trim() {
+        "application/zip" | "application/x-zip-compressed" => Some(ArchiveFormat::Zip),
+        "application/x-tar" => Some(ArchiveFormat::Tar),
+        "application/x-compressed-tar" => Some(ArchiveFormat::TarGz),
+        "application/x-zstd-compressed-tar" => Some(ArchiveFormat::TarZst),
+        "application/gzip" | "application/zstd" => format_for_name(name),
+        _ => None,
+    }
+}
+pub fn archive_stem(name: &str) -> String {//name without any archive extension, for the folder it extracts into
+    let lower = name.to_lowercase();
+    for ext in [".tar.gz", ".tar.zst", ".tgz", ".tzst", ".tar", ".zip"] {
+        if lower.ends_with(ext) {
+            return name[..name.len() - ext.len()].to_string();
+        }
+    }
+    name.to_string()
+}
+fn io_err(path: &Path) -> impl FnOnce(io::Error) -> FileOpError + '_ {
+    move |e| FileOpError::Io(path.to_path_buf(), e)
+}
+fn safe_join(dest_dir: &Path, entry: &Path) -> Result<PathBuf, FileOpError> {//resolve an archive entry inside dest_dir, refusing absolute paths and anything that climbs out with ..
+    let mut output = dest_dir.to_path_buf();
+    for component in entry.components() {
+        match component {
+            Component::Normal(x) => output.push(x),
+            Component::CurDir => {}
+            _ => return Err(FileOpError::InvalidName(entry.to_string_lossy().to_string())),
+        }
+    }
+    let mut ancestor = output.parent();
+    while let Some(x) = ancestor {//a symlink unpacked earlier could still point writes outside of dest_dir
+        if x == dest_dir || !x.starts_with(dest_dir) {
+            break;
+        }
+        if fs::symlink_metadata(x).map(|m| m.is_symlink()).unwrap_or(false) {
+            return Err(FileOpError::InvalidName(entry.to_string_lossy().to_string()));
+        }
+        ancestor = x.parent();
+    }
+    Ok(output)
+}
+fn copy_chunked(input: &mut dyn Read, output: &mut dyn Write, path: &Path, progress: Option<&JobProgress>, count_bytes: bool) -> Result<(), FileOpError> {//stream one entry, checking in with the job between chunks
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        if let Some(x) = progress {
+            x.checkpoint()?;
+        }
+        let read = input.read(&mut buffer).map_err(io_err(path))?;
+        if read == 0 {
+            return Ok(());
+        }
+        output.write_all(&buffer[..read]).map_err(io_err(path))?;
+        if let (Some(x), true) = (progress, count_bytes) {
+            x.add_bytes(read as u64);
+        }
+    }
+}
+struct CountingReader<'a, R: Read> {//counts compressed bytes as they are read, that is the only size a tarball knows up front
+    inner: R,
+    progress: Option<&'a JobProgress>,
+}
+impl<'a, R: Read> Read for CountingReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let Some(x) = self.progress {
+            x.add_bytes(read as u64);
+        }
+        Ok(read)
+    }
+}
+fn collect_entries(sources: &[PathBuf]) -> Result<Vec<(PathBuf, PathBuf)>, FileOpError> {//every path to archive, paired with its name inside the archive relative to the source's parent
+    let mut entries = vec![];
+    let mut stack: Vec<(PathBuf, PathBuf)> = vec![];
+    for source in sources.iter().rev() {
+        match source.file_name() {
+            Some(name) => stack.push((source.clone(), PathBuf::from(name))),
+            None => return Err(FileOpError::InvalidName(source.to_string_lossy().to_string())),
+        }
+    }
+    while let Some((path, name)) = stack.pop() {
+        let metadata = fs::symlink_metadata(&path).map_err(io_err(&path))?;
+        if metadata.is_dir() {
+            let mut children: Vec<PathBuf> = fs::read_dir(&path).map_err(io_err(&path))?.flatten().map(|x| x.path()).collect();
+            children.sort();
+            for child in children.into_iter().rev() {
+                let child_name = name.join(child.file_name().unwrap_or_default());
+                stack.push((child, child_name));
+            }
+        }
+        entries.push((path, name));
+    }
+    Ok(entries)
+}
+fn write_tar<W: Write>(writer: W, dest: &Path, entries: &[(PathBuf, PathBuf)], progress: Option<&JobProgress>) -> Result<W, FileOpError> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    for (path, name) in entries {
+        if let Some(x) = progress {
+            x.checkpoint()?;
+            x.set_current(path);
+        }
+        builder.append_path_with_name(path, name).map_err(io_err(path))?;
+        if let Some(x) = progress {
+            let metadata = fs::symlink_metadata(path).map_err(io_err(path))?;
+            if metadata.is_file() {
+                x.add_bytes(metadata.len());
+            }
+            x.finish_item();
+        }
+    }
+    builder.into_inner().map_err(io_err(dest))
+}
+fn write_zip(file: fs::File, dest: &Path, entries: &[(PathBuf, PathBuf)], progress: Option<&JobProgress>) -> Result<(), FileOpError> {
+    let mut writer = zip::ZipWriter::new(file);
+    for (path, name) in entries {
+        if let Some(x) = progress {
+            x.checkpoint()?;
+            x.set_current(path);
+        }
+        let metadata = fs::symlink_metadata(path).map_err(io_err(path))?;
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated).unix_permissions(metadata.permissions().mode() & 0o777);
+        let name = name.to_string_lossy().to_string();
+        let zip_err = |e: zip::result::ZipError| FileOpError::Io(path.to_path_buf(), io::Error::other(e));
+        if metadata.is_symlink() {
+            let target = fs::read_link(path).map_err(io_err(path))?;
+            writer.add_symlink(name, target.to_string_lossy(), options).map_err(zip_err)?;
+        } else if metadata.is_dir() {
+            writer.add_directory(name, options).map_err(zip_err)?;
+        } else {
+            writer.start_file(name, options).map_err(zip_err)?;
+            let mut input = fs::File::open(path).map_err(io_err(path))?;
+            copy_chunked(&mut input, &mut writer, path, progress, true)?;
+        }
+        if let Some(x) = progress {
+            x.finish_item();
+        }
+    }
+    writer.finish().map_err(|e| FileOpError::Io(dest.to_path_buf(), io::Error::other(e)))?;
+    Ok(())
+}
+pub fn compress(sources: &[PathBuf], dest: &Path, progress: Option<&JobProgress>) -> Result<(), FileOpError> {//pack sources into a new archive at dest, the format follows dest's extension
+    let format = match format_for_name(&dest.file_name().unwrap_or_default().to_string_lossy()) {
+        Some(x) => x,
+        None => return Err(FileOpError::InvalidValue(dest.to_string_lossy().to_string(), String::from("archive name, use .zip, .tar, .tar.gz or .tar.zst"))),
+    };
+    for source in sources {
+        if dest.starts_with(source) {
+            return Err(FileOpError::IntoItself(source.clone()));
+        }
+    }
+    let entries = collect_entries(sources)?;
+    if let Some(x) = progress {
+        for source in sources {
+            let (bytes, items) = measure(source);
+            x.bytes_total.fetch_add(bytes, Ordering::Relaxed);
+            x.items_total.fetch_add(items, Ordering::Relaxed);
+        }
+    }
+    let file = fs::OpenOptions::new().write(true).create_new(true).open(dest).map_err(io_err(dest))?;
+    let result = match format {
+        ArchiveFormat::Zip => write_zip(file, dest, &entries, progress),
+        ArchiveFormat::Tar => write_tar(file, dest, &entries, progress).map(|_| ()),
+        ArchiveFormat::TarGz => write_tar(GzEncoder::new(file, Compression::default()), dest, &entries, progress).and_then(|x| x.finish().map(|_| ()).map_err(io_err(dest))),
+        ArchiveFormat::TarZst => zstd::Encoder::new(file, 0).map_err(io_err(dest))
+            .and_then(|x| write_tar(x, dest, &entries, progress))
+            .and_then(|x| x.finish().map(|_| ()).map_err(io_err(dest))),
+    };
+    if result.is_err() {//don't leave half an archive lying around
+        let _ = fs::remove_file(dest);
+    }
+    result
+}
+fn unpack_tar<R: Read>(reader: R, dest_dir: &Path, progress: Option<&JobProgress>) -> Result<(), FileOpError> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(io_err(dest_dir))? {
+        let mut entry = entry.map_err(io_err(dest_dir))?;
+        let name = entry.path().map_err(io_err(dest_dir))?.to_path_buf();
+        let target = safe_join(dest_dir, &name)?;
+        if let Some(x) = progress {
+            x.checkpoint()?;
+            x.set_current(&target);
+        }
+        let mode = entry.header().mode().unwrap_or(0o644) & 0o777;//never carry setuid and friends over
+        match entry.header().entry_type() {
+            tar::EntryType::Directory => fs::create_dir_all(&target).map_err(io_err(&target))?,
+            tar::EntryType::Symlink => {
+                let link = entry.link_name().map_err(io_err(&target))?.unwrap_or_default().to_path_buf();
+                make_parent(&target)?;
+                symlink(link, &target).map_err(io_err(&target))?;
+            }
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                make_parent(&target)?;
+                let mut output = fs::OpenOptions::new().write(true).create_new(true).open(&target).map_err(io_err(&target))?;
+                copy_chunked(&mut entry, &mut output, &target, progress, false)?;
+                fs::set_permissions(&target, fs::Permissions::from_mode(mode)).map_err(io_err(&target))?;
+            }
+            _ => continue,//hard links, devices and fifos are skipped
+        }
+        if let Some(x) = progress {
+            x.finish_item();
+        }
+    }
+    Ok(())
+}
+fn unpack_zip(file: fs::File, dest_dir: &Path, progress: Option<&JobProgress>) -> Result<(), FileOpError> {
+    let zip_err = |e: zip::result::ZipError| FileOpError::Io(dest_dir.to_path_buf(), io::Error::other(e));
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_err)?;
+    if let Some(x) = progress {
+        for i in 0..archive.len() {
+            if let Ok(entry) = archive.by_index_raw(i) {
+                x.bytes_total.fetch_add(entry.size(), Ordering::Relaxed);
+            }
+        }
+    }
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(zip_err)?;
+        let target = safe_join(dest_dir, Path::new(entry.name()))?;
+        if let Some(x) = progress {
+            x.checkpoint()?;
+            x.set_current(&target);
+        }
+        let mode = entry.unix_mode().unwrap_or(0o644);
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(io_err(&target))?;
+        } else if mode & TYPE_MASK == SYMLINK_MODE {
+            let mut link = String::new();
+            entry.read_to_string(&mut link).map_err(io_err(&target))?;
+            if let Some(x) = progress {
+                x.add_bytes(link.len() as u64);
+            }
+            make_parent(&target)?;
+            symlink(link, &target).map_err(io_err(&target))?;
+        } else {
+            make_parent(&target)?;
+            let mut output = fs::OpenOptions::new().write(true).create_new(true).open(&target).map_err(io_err(&target))?;
+            copy_chunked(&mut entry, &mut output, &target, progress, true)?;
+            fs::set_permissions(&target, fs::Permissions::from_mode(mode & 0o777)).map_err(io_err(&target))?;
+        }
+        if let Some(x) = progress {
+            x.finish_item();
+        }
+    }
+    Ok(())
+}
+fn make_parent(target: &Path) -> Result<(), FileOpError> {
+    match target.parent() {
+        Some(x) => fs::create_dir_all(x).map_err(io_err(x)),
+        None => Ok(()),
+    }
+}
+fn unpack(archive: &Path, format: ArchiveFormat, dest_dir: &Path, progress: Option<&JobProgress>) -> Result<(), FileOpError> {
+    let file = fs::File::open(archive).map_err(io_err(archive))?;
+    if format != ArchiveFormat::Zip {
+        if let (Some(x), Ok(metadata)) = (progress, file.metadata()) {
+            x.bytes_total.fetch_add(metadata.len(), Ordering::Relaxed);
+        }
+    }
+    let counted = CountingReader { inner: file, progress };
+    match format {
+        ArchiveFormat::Zip => unpack_zip(counted.inner, dest_dir, progress),
+        ArchiveFormat::Tar => unpack_tar(counted, dest_dir, progress),
+        ArchiveFormat::TarGz => unpack_tar(GzDecoder::new(counted), dest_dir, progress),
+        ArchiveFormat::TarZst => unpack_tar(zstd::Decoder::new(counted).map_err(io_err(archive))?, dest_dir, progress),
+    }
+}
+pub fn extract(archive: &Path, format: ArchiveFormat, dest: &Path, unwrap: bool, progress: Option<&JobProgress>) -> Result<PathBuf, FileOpError> {//unpack into a fresh directory at dest, or straight next to it if unwrap is set and the archive holds a single entry
+    let parent = dest.parent().unwrap_or(Path::new("/")).to_path_buf();
+    fs::create_dir_all(&parent).map_err(io_err(&parent))?;
+    let name = archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let temp = parent.join(unique_name(&parent, &format!(".narwhal-extract-{}", name)));//unpack out of sight, so a failure leaves nothing behind
+    fs::create_dir(&temp).map_err(io_err(&temp))?;
+    if let Err(e) = unpack(archive, format, &temp, progress) {
+        let _ = remove_path(&temp);
+        return Err(e);
+    }
+    let entries: Vec<PathBuf> = match fs::read_dir(&temp) {
+        Ok(x) => x.flatten().map(|x| x.path()).collect(),
+        Err(..) => vec![],
+    };
+    let result = match (unwrap, entries.as_slice()) {
+        (true, [single]) => {
+            let inner_name = single.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let created = parent.join(unique_name(&parent, &inner_name));
+            move_into_place(single, &created).and_then(|_| fs::remove_dir(&temp).map_err(io_err(&temp))).map(|_| created)
+        }
+        _ => move_into_place(&temp, dest).map(|_| dest.to_path_buf()),
+    };
+    if result.is_err() {
+        let _ = remove_path(&temp);
+    }
+    result
+}
+pub fn run_compress(sources: Vec<PathBuf>, dest: PathBuf, progress: Option<&JobProgress>) -> JobOutcome {//compress as a job, journaling the archive it made
+    match compress(&sources, &dest, progress) {
+        Ok(..) => JobOutcome { ops: vec![JournalOp::Compress { sources: sources.iter().map(|x| x.to_string_lossy().to_string()).collect(), created: dest.to_string_lossy().to_string() }], errors: vec![] },
+        Err(e) => JobOutcome { ops: vec![], errors: vec![e] },
+    }
+}
+pub fn run_extract(archives: Vec<(PathBuf, ArchiveFormat)>, dest_dir: PathBuf, progress: Option<&JobProgress>) -> JobOutcome {//extract every archive into its own folder in dest_dir, carrying on past failures
+    let mut ops = vec![];
+    let mut errors = vec![];
+    for (archive, format) in archives {
+        let name = archive.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let dest = dest_dir.join(unique_name(&dest_dir, &archive_stem(&name)));
+        match extract(&archive, format, &dest, true, progress) {
+            Ok(created) => ops.push(JournalOp::Extract { archive: archive.to_string_lossy().to_string(), created: created.to_string_lossy().to_string(), format, unwrap: true }),
+            Err(FileOpError::Cancelled) => {
+                errors.push(FileOpError::Cancelled);
+                break;
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    JobOutcome { ops, errors }
+}
+fn mirrors_home() -> PathBuf {//every running instance keeps its mirrors apart, so closing one can't pull a mirror out from under another
+    PathBuf::from(format!("{}/NarwhalFM-archives/{}", get_cache_home(), std::process::id()))
+}
+pub fn mirror_path(archive: &Path) -> Result<PathBuf, FileOpError> {//where an archive gets unpacked for browsing, a new one for every change to the archive
+    let metadata = fs::metadata(archive).map_err(io_err(archive))?;
+    let mut hasher = DefaultHasher::new();
+    archive.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    Ok(mirrors_home().join(format!("{:016x}", hasher.finish())))
+}
+pub fn run_mirror(archive: PathBuf, format: ArchiveFormat, mirror: PathBuf, progress: Option<&JobProgress>) -> JobOutcome {//unpack an archive for browsing as a job, nothing to journal since mirrors are thrown away with the session
+    match extract(&archive, format, &mirror, false, progress) {
+        Ok(..) => JobOutcome { ops: vec![], errors: vec![] },
+        Err(e) => JobOutcome { ops: vec![], errors: vec![e] },
+    }
+}
+pub fn clear_mirrors() {//mirrors only live as long as the session, the clipboard may point into them until then
+    let _ = fs::remove_dir_all(mirrors_home());
+    let left_over = match fs::read_dir(format!("{}/NarwhalFM-archives", get_cache_home())) {
+        Ok(x) => x.flatten().map(|x| x.file_name().to_string_lossy().to_string()).collect(),
+        Err(..) => vec![],
+    };
+    for pid in left_over {//an instance that crashed never got to clean up after itself
+        if !Path::new("/proc").join(&pid).exists() {
+            let _ = fs::remove_dir_all(format!("{}/NarwhalFM-archives/{}", get_cache_home(), pid));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use zip::write::{FileOptions, ZipWriter};
+
+    const ESCAPES: [&str; 3] = ["../x", "/etc/x", "a/../../x"];
+
+    fn scratch_dir(name: &str) -> PathBuf {//dest is where archives unpack, anything showing up next to it got out
+        let dir = env::temp_dir().join(format!("narwhal-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("dest")).unwrap();
+        dir
+    }
+    fn tar_header(name: &str, entry_type: tar::EntryType, size: u64) -> tar::Header {//names go in raw, set_path would refuse the very names we want to test
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_mode(0o644);
+        header.set_size(size);
+        header
+    }
+    fn tar_file(builder: &mut tar::Builder<Vec<u8>>, name: &str) {
+        let mut header = tar_header(name, tar::EntryType::Regular, 1);
+        header.set_cksum();
+        builder.append(&header, &b"x"[..]).unwrap();
+    }
+    fn zip_archive(dir: &Path, build: impl FnOnce(&mut ZipWriter<fs::File>)) -> fs::File {
+        let path = dir.join("test.zip");
+        let mut writer = ZipWriter::new(fs::File::create(&path).unwrap());
+        build(&mut writer);
+        writer.finish().unwrap();
+        fs::File::open(path).unwrap()
+    }
+
+    #[test]
+    fn safe_join_refuses_escapes() {
+        let dest = Path::new("/tmp/dest");
+        for name in ESCAPES {
+            assert!(safe_join(dest, Path::new(name)).is_err(), "{}", name);
+        }
+        assert_eq!(safe_join(dest, Path::new("./a/b")).unwrap(), dest.join("a/b"));
+    }
+    #[test]
+    fn tar_refuses_escapes() {
+        let dir = scratch_dir("tar-escape");
+        for name in ESCAPES {
+            let mut builder = tar::Builder::new(vec![]);
+            tar_file(&mut builder, name);
+            let data = builder.into_inner().unwrap();
+            assert!(unpack_tar(data.as_slice(), &dir.join("dest"), None).is_err(), "{}", name);
+        }
+        assert!(!dir.join("x").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn tar_refuses_writes_through_symlinks() {
+        let dir = scratch_dir("tar-symlink");
+        fs::create_dir(dir.join("outside")).unwrap();
+        let mut builder = tar::Builder::new(vec![]);
+        let mut header = tar_header("link", tar::EntryType::Symlink, 0);
+        header.set_link_name(dir.join("outside")).unwrap();
+        header.set_cksum();
+        builder.append(&header, io::empty()).unwrap();
+        tar_file(&mut builder, "link/x");
+        let data = builder.into_inner().unwrap();
+        assert!(unpack_tar(data.as_slice(), &dir.join("dest"), None).is_err());
+        assert!(!dir.join("outside/x").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn zip_refuses_escapes() {
+        let dir = scratch_dir("zip-escape");
+        for name in ESCAPES {
+            let file = zip_archive(&dir, |writer| {
+                writer.start_file(name, FileOptions::default()).unwrap();
+                writer.write_all(b"x").unwrap();
+            });
+            assert!(unpack_zip(file, &dir.join("dest"), None).is_err(), "{}", name);
+        }
+        assert!(!dir.join("x").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn zip_refuses_writes_through_symlinks() {
+        let dir = scratch_dir("zip-symlink");
+        fs::create_dir(dir.join("outside")).unwrap();
+        let outside = dir.join("outside").to_string_lossy().to_string();
+        let file = zip_archive(&dir, |writer| {
+            writer.add_symlink("link", outside, FileOptions::default()).unwrap();
+            writer.start_file("link/x", FileOptions::default()).unwrap();
+            writer.write_all(b"x").unwrap();
+        });
+        assert!(unpack_zip(file, &dir.join("dest"), None).is_err());
+        assert!(!dir.join("outside/x").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 use std::env;
+use std::fs;
 use serde_derive::{Serialize, Deserialize};
 use std::collections::HashMap;
 
@@ -12,6 +13,31 @@ pub fn get_cache_home() -> String { //get cache directory in compliance with XDG
     }
 }
 
+pub fn get_data_home() -> String {//get the user's data home, in compliance with XDG directories
+    match env::var("XDG_DATA_HOME") {
+        Ok(x) => x,
+        Err(..) => match env::var("HOME") {
+            Ok(x) => format!("{x}/.local/share"),
+            Err(..) => panic!("bailing out, you're on your own")
+        }
+    }
+}
+
+pub fn get_templates_dir() -> Option<String> {//get the user's templates directory from user-dirs.dirs, None if it is switched off
+    let home = env::var("HOME").ok()?;
+    let user_dirs = fs::read_to_string(format!("{}/user-dirs.dirs", get_config_home())).unwrap_or_default();
+    let mut dir = format!("{home}/Templates");
+    for line in user_dirs.lines() {
+        if let Some(value) = line.trim().strip_prefix("XDG_TEMPLATES_DIR=") {
+            dir = value.trim_matches('"').replace("$HOME", &home);
+        }
+    }
+    match dir.trim_end_matches('/') == home.trim_end_matches('/') {//the spec turns a directory off by pointing it at home
+        true => None,
+        false => Some(dir),
+    }
+}
+
 pub fn encode_sort(sort_type: SortType) -> String {//convert a sort type to a string
     match sort_type {
         SortType::Alphabetical => "Alphabetical".to_string(),
@@ -30,6 +56,36 @@ pub fn decode_sort(sort_type: String) -> SortType {//convert a string into a sor
         &_ => SortType::Folders
     }
 }
+pub fn encode_view(view_mode: ViewMode) -> String {//convert a view mode to a string
+    match view_mode {
+        ViewMode::Grid => "Grid".to_string(),
+        ViewMode::Miller => "Miller".to_string(),
+    }
+}
+pub fn decode_view(view_mode: String) -> ViewMode {//convert a string into a view mode
+    match view_mode.as_str() {
+        "Miller" => ViewMode::Miller,
+        &_ => ViewMode::Grid
+    }
+}
+pub fn encode_conflict(policy: ConflictPolicy) -> String {//convert a conflict policy to a string
+    match policy {
+        ConflictPolicy::Ask => "Ask".to_string(),
+        ConflictPolicy::Overwrite => "Overwrite".to_string(),
+        ConflictPolicy::Skip => "Skip".to_string(),
+        ConflictPolicy::KeepBoth => "KeepBoth".to_string(),
+        ConflictPolicy::Merge => "Merge".to_string(),
+    }
+}
+pub fn decode_conflict(policy: String) -> ConflictPolicy {//convert a string into a conflict policy
+    match policy.as_str() {
+        "Overwrite" => ConflictPolicy::Overwrite,
+        "Skip" => ConflictPolicy::Skip,
+        "KeepBoth" => ConflictPolicy::KeepBoth,
+        "Merge" => ConflictPolicy::Merge,
+        &_ => ConflictPolicy::Ask
+    }
+}
 pub fn get_config_home() -> String {//get the user's config home, in compliance with XDG directories
     match env::var("XDG_CONFIG_HOME") {
         Ok(x) => x,
@@ -57,7 +113,21 @@ pub struct Config {//struct representation of a config file
     pub show_hidden: bool,
     pub bookmarks: Vec<BookmarkDir>,
     pub icn_theme: String,
-    pub icn_size: u16
+    pub icn_size: u16,
+    #[serde(default)]
+    pub conflict_policy: String,
+    #[serde(default)]
+    pub tabs: Vec<SavedTab>,
+    #[serde(default)]
+    pub active_tab: usize,
+    #[serde(default)]
+    pub view_mode: String,
+}
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedTab {//struct representation of a tab to reopen on the next launch
+    pub path: String,
+    pub sort_mode: String,
+    pub show_hidden: bool,
 }
 #[derive(Serialize, Deserialize, Clone)]
 pub struct CacheFile {//struct representation of the cache file
@@ -70,6 +140,19 @@ pub enum SortType { //enum representing what sort type is preferred
     Folders,
     Files,
 }
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ViewMode {//enum representing how a folder is laid out
+    Grid,
+    Miller,//ranger style parent, current and preview columns
+}
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConflictPolicy {//enum representing what to do when something already sits at the destination
+    Ask,
+    Overwrite,
+    Skip,
+    KeepBoth,
+    Merge,
+}
 #[derive(Serialize, Deserialize)]
 pub struct CuttlefishCfg {//struct used in collecting the user's preferred theme
     pub theme: String
@@ -1,19 +1,24 @@
-use crate::Narwhal;
-use crate::sort_file_by_type;
+use crate::{EST_HEIGHT, Narwhal};
 use std::str::FromStr;
 use std::{env, fs};
+use std::path::{Path, PathBuf};
 use cosmic_time::Timeline;
-use iced::widget::text_input;
+use iced::widget::{scrollable, text_input};
 use toml;
 use crate::CacheFile;
 use std::collections::HashMap;
 use crate::get_cache_home;
 use crate::get_config_home;
 use crate::Config;
+use crate::history_helpers::{History, Location};
+use crate::tab_helpers::TabState;
+use crate::journal_helpers::Journal;
+use crate::selection_helpers::Selection;
 use crate::decode_sort;
+use crate::decode_conflict;
+use crate::decode_view;
 use iced::Color;
 
-use iced::futures::executor::block_on;
 use iced_style::theme;
 use oceania_style::{ButtonStyle, get_set_theme, ListStyle, make_custom_theme, MenuStyle, ThemeCustom, ThemeSet};
 
@@ -33,7 +38,7 @@ impl Default for Narwhal {
         let config_text = fs::read_to_string(config_home);
         let config_struct: Config = match config_text {
             Ok(x) => toml::from_str(&x).unwrap(),
-            Err(..) => Config { sort_mode: "Folder".to_string(), show_hidden: false, bookmarks: vec![], icn_theme: String::from_str("Adwaita").unwrap(), icn_size: 32 }
+            Err(..) => Config { sort_mode: "Folder".to_string(), show_hidden: false, bookmarks: vec![], icn_theme: String::from_str("Adwaita").unwrap(), icn_size: 32, conflict_policy: String::from("Ask"), tabs: vec![], active_tab: 0, view_mode: String::from("Grid") }
         };
         let mut final_struct = Narwhal {//build a struct with only config options injected
             files: vec![], 
@@ -42,13 +47,13 @@ impl Default for Narwhal {
             desired_cols: 5, 
             show_hidden: config_struct.show_hidden, 
             desired_rows: 5, 
-            last_clicked_file: None, 
+            selection: Selection::default(),
+            modifiers: iced::keyboard::Modifiers::default(),
             ui_files: vec![],
             icon_cache: cache_struct.contents.clone(), 
             bookmarked_dirs: config_struct.bookmarks.clone(), 
             deletion_confirmation: false, 
-            mv_target: None, 
-            cp_target: None,
+            clipboard: None,
             theme: get_set_theme(),
             type_mode: None,
             rename_id: text_input::Id::unique(),
@@ -56,7 +61,50 @@ impl Default for Narwhal {
             anims: Timeline::new(),
             icn_theme: config_struct.icn_theme.clone(),
             icn_size: config_struct.icn_size,
+            conflict_policy: decode_conflict(config_struct.conflict_policy.clone()),
+            pending_transfer: None,
+            batch_rename: None,
+            editor_rename: None,
+            templates: None,
+            properties: None,
+            size_scan: None,
+            size_cache: HashMap::new(),
+            archive_prompt: None,
+            archive_stack: vec![],
+            opening_archive: None,
+            cursor_position: None,
+            drop_hover: vec![],
+            dropped: vec![],
+            dropped_at: None,
+            drop_target: None,
+            drag: None,
+            history: History::default(),
+            prompt_id: text_input::Id::unique(),
+            tabs: vec![],
+            active_tab: 0,
+            window_cols: 5,
+            split: None,
+            focus_right: false,
+            view_mode: decode_view(config_struct.view_mode.clone()),
+            parent_files: vec![],
+            miller_key: None,
+            preview: None,
+            miller_id: scrollable::Id::unique(),
+            grid_id: scrollable::Id::unique(),
+            grid_offset: 0.0,
+            grid_height: 5.0 * EST_HEIGHT as f32,
+            ui_start_row: 0,
+            ui_end_row: 0,
+            location_edit: None,
+            location_id: text_input::Id::unique(),
             show_file_options: true,
+            op_error: None,
+            trash_view: false,
+            trash_entries: HashMap::new(),
+            empty_confirmation: false,
+            journal: Journal::load(),
+            jobs: vec![],
+            next_job_id: 0,
             themes: ThemeSet {
             light: ThemeCustom {
                 application: theme::Palette {
@@ -145,9 +193,16 @@ impl Default for Narwhal {
             custom: make_custom_theme()
         },
         };
-        final_struct.regen_files();//generate file list
-        sort_file_by_type(&mut final_struct.files, final_struct.sort_type.clone());//sort file list
-        block_on(final_struct.regen_ui_files());//regenerate ui files
+        let saved_tabs: Vec<TabState> = config_struct.tabs.iter().filter(|x| Path::new(&x.path).is_dir()).map(|x| {//reopen last session's tabs, skipping any whose folder is gone
+            TabState::new(Location { path: PathBuf::from(&x.path), trash: false, archives: vec![] }, decode_sort(x.sort_mode.clone()), x.show_hidden)
+        }).collect();
+        final_struct.tabs = vec![final_struct.snapshot_tab()];
+        if !saved_tabs.is_empty() {
+            final_struct.active_tab = config_struct.active_tab.min(saved_tabs.len() - 1);
+            final_struct.tabs = saved_tabs;
+        }
+        let tab = final_struct.tabs[final_struct.active_tab].clone();
+        final_struct.load_tab(tab);//generate, sort and render the file list
         final_struct
     }
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use iced::Point;
+use crate::{EST_HEIGHT, EST_LENGTH, RULE_WIDTH, SIDEBAR_WIDTH, SPACING, TOP_HEIGHT};
+
+const SIDEBAR_FIXED_ROWS: usize = 3;//make folder, make file and the template button sit above the templates and bookmarks
+const DRAG_THRESHOLD: f32 = 6.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DropTarget {//enum representing what something dragged over the window would land in
+    Tile(usize),//index into files
+    Bookmark(usize),
+    OtherPane,//anywhere over the pane without focus lands in its folder
+}
+pub struct DragState {//a tile being dragged around inside the window
+    pub index: usize,//the tile the press started on
+    pub paths: Vec<PathBuf>,
+    pub origin: Option<Point>,
+    pub moved: bool,//until the cursor goes some distance this is still just a click
+}
+
+pub fn moved_far(origin: Point, position: Point) -> bool {
+    origin.distance(position) > DRAG_THRESHOLD
+}
+pub fn tile_at(position: Point, offset: u32, scroll: f32, cols: u32, tiles: usize) -> Option<usize> {//which grid tile is under position, as a position in display order, gaps between tiles count as nothing
+    let x = position.x - (SIDEBAR_WIDTH + RULE_WIDTH) as f32 - offset as f32;
+    let y = position.y - (2 * TOP_HEIGHT + RULE_WIDTH) as f32;//the tab strip and the function buttons sit above the grid
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+    let col = (x / EST_LENGTH as f32) as usize;
+    let row = ((y + scroll) / EST_HEIGHT as f32) as usize;
+    if col >= cols as usize || x % EST_LENGTH as f32 > (EST_LENGTH - SPACING as u32) as f32 {
+        return None;
+    }
+    let index = row * cols as usize + col;
+    match index < tiles {
+        true => Some(index),
+        false => None,
+    }
+}
+pub fn bookmark_at(position: Point, templates: usize, bookmarks: usize) -> Option<usize> {//which bookmark button is under position, every sidebar button is TOP_HEIGHT tall
+    if position.x < 0.0 || position.x > SIDEBAR_WIDTH as f32 || position.y < 0.0 {
+        return None;
+    }
+    let row = (position.y / TOP_HEIGHT as f32) as usize;
+    match row.checked_sub(SIDEBAR_FIXED_ROWS + templates) {
+        Some(x) if x < bookmarks => Some(x),
+        _ => None,
+    }
+}
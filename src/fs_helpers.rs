@@ -0,0 +1,289 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use crate::job_helpers::JobProgress;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug)]
+pub enum FileOpError {//enum representing every way a file operation can fail
+    NotFound(PathBuf),
+    AlreadyExists(PathBuf),
+    IntoItself(PathBuf),
+    InvalidName(String),
+    InvalidValue(String, String),
+    Changed(PathBuf),
+    NothingToDo,
+    Cancelled,
+    Io(PathBuf, io::Error),
+}
+impl fmt::Display for FileOpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileOpError::NotFound(path) => write!(f, "{} does not exist", path.display()),
+            FileOpError::AlreadyExists(path) => write!(f, "{} already exists", path.display()),
+            FileOpError::IntoItself(path) => write!(f, "cannot put {} inside of itself", path.display()),
+            FileOpError::InvalidName(name) => write!(f, "\"{}\" is not a valid file name", name),
+            FileOpError::InvalidValue(value, what) => write!(f, "\"{}\" is not a valid {}", value, what),
+            FileOpError::Changed(path) => write!(f, "{} changed since the operation, refusing to touch it", path.display()),
+            FileOpError::NothingToDo => write!(f, "nothing to do"),
+            FileOpError::Cancelled => write!(f, "cancelled"),
+            FileOpError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+impl std::error::Error for FileOpError {}
+
+fn io_err(path: &Path) -> impl FnOnce(io::Error) -> FileOpError + '_ {//wrap an io error with the path it happened on
+    move |e| FileOpError::Io(path.to_path_buf(), e)
+}
+fn check_source(src: &Path) -> Result<(), FileOpError> {//make sure the source exists, without following symlinks
+    match fs::symlink_metadata(src) {
+        Ok(..) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(FileOpError::NotFound(src.to_path_buf())),
+        Err(e) => Err(FileOpError::Io(src.to_path_buf(), e)),
+    }
+}
+fn check_free(dest: &Path) -> Result<(), FileOpError> {//make sure nothing already sits at the destination
+    match fs::symlink_metadata(dest) {
+        Ok(..) => Err(FileOpError::AlreadyExists(dest.to_path_buf())),
+        Err(..) => Ok(()),
+    }
+}
+fn check_not_inside(src: &Path, dest: &Path) -> Result<(), FileOpError> {//a directory copied or moved into itself would never stop growing, symlinks along dest's way are resolved first
+    if !fs::symlink_metadata(src).map(|x| x.is_dir()).unwrap_or(false) {
+        return Ok(());
+    }
+    let src_real = fs::canonicalize(src).unwrap_or(src.to_path_buf());
+    let dest_real = match (dest.parent().and_then(|x| fs::canonicalize(x).ok()), dest.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => dest.to_path_buf(),
+    };
+    match dest_real.starts_with(&src_real) {
+        true => Err(FileOpError::IntoItself(src.to_path_buf())),
+        false => Ok(()),
+    }
+}
+pub fn validate_name(name: &str) -> Result<(), FileOpError> {//reject names that cannot be a single path component
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\0') {
+        Err(FileOpError::InvalidName(name.to_string()))
+    } else {
+        Ok(())
+    }
+}
+fn copy_file_chunked(src: &Path, dest: &Path, progress: &JobProgress) -> Result<(), FileOpError> {//copy a single file in chunks so a job can report on it, pause it and cancel it
+    let mut input = fs::File::open(src).map_err(io_err(src))?;
+    let mut output = fs::OpenOptions::new().write(true).create_new(true).open(dest).map_err(io_err(dest))?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        if let Err(e) = progress.checkpoint() {
+            drop(output);
+            let _ = fs::remove_file(dest);
+            return Err(e);
+        }
+        let read = input.read(&mut buffer).map_err(io_err(src))?;
+        if read == 0 {
+            break;
+        }
+        output.write_all(&buffer[..read]).map_err(io_err(dest))?;
+        progress.add_bytes(read as u64);
+    }
+    let permissions = input.metadata().map_err(io_err(src))?.permissions();
+    fs::set_permissions(dest, permissions).map_err(io_err(dest))
+}
+fn copy_recursive(src: &Path, dest: &Path, progress: Option<&JobProgress>) -> Result<(), FileOpError> {//copy src to dest, descending into directories and preserving symlinks
+    if let Some(x) = progress {
+        x.checkpoint()?;
+        x.set_current(src);
+    }
+    let metadata = fs::symlink_metadata(src).map_err(io_err(src))?;
+    if metadata.is_symlink() {
+        let target = fs::read_link(src).map_err(io_err(src))?;
+        symlink(target, dest).map_err(io_err(dest))?;
+    } else if metadata.is_dir() {
+        fs::create_dir(dest).map_err(io_err(dest))?;
+        for entry in fs::read_dir(src).map_err(io_err(src))? {
+            let entry = entry.map_err(io_err(src))?;
+            copy_recursive(&entry.path(), &dest.join(entry.file_name()), progress)?;
+        }
+        fs::set_permissions(dest, metadata.permissions()).map_err(io_err(dest))?;
+    } else {
+        match progress {
+            Some(x) => copy_file_chunked(src, dest, x)?,
+            None => {
+                fs::copy(src, dest).map_err(io_err(src))?;
+            }
+        }
+    }
+    if let Some(x) = progress {
+        x.finish_item();
+    }
+    Ok(())
+}
+pub fn copy_into_place(src: &Path, dest: &Path) -> Result<(), FileOpError> {//copy src to exactly dest
+    copy_into_place_with(src, dest, None)
+}
+pub fn copy_into_place_with(src: &Path, dest: &Path, progress: Option<&JobProgress>) -> Result<(), FileOpError> {//copy_into_place, reporting to a background job if there is one
+    check_source(src)?;
+    check_free(dest)?;
+    check_not_inside(src, dest)?;
+    if let Err(e) = copy_recursive(src, dest, progress) {//don't leave half a copy lying around
+        let _ = remove_path(dest);
+        return Err(e);
+    }
+    Ok(())
+}
+pub fn remove_path(path: &Path) -> Result<(), FileOpError> {//permanently delete a file or directory tree
+    let metadata = fs::symlink_metadata(path).map_err(io_err(path))?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path).map_err(io_err(path))
+    } else {
+        fs::remove_file(path).map_err(io_err(path))
+    }
+}
+pub fn unique_name(dir: &Path, name: &str) -> String {//name itself if free in dir, otherwise "stem (n).ext" with the first free n
+    if fs::symlink_metadata(dir.join(name)).is_err() {
+        return name.to_string();
+    }
+    let (stem, ext) = split_name(name);
+    let mut counter = 1;
+    loop {
+        let candidate = format!("{stem} ({counter}){ext}");
+        if fs::symlink_metadata(dir.join(&candidate)).is_err() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+pub fn numbered_name(dir: &Path, base: &str) -> String {//base itself if free in dir, otherwise "base n" with the first free n from 2 up
+    if fs::symlink_metadata(dir.join(base)).is_err() {
+        return base.to_string();
+    }
+    let mut counter = 2;
+    loop {
+        let candidate = format!("{base} {counter}");
+        if fs::symlink_metadata(dir.join(&candidate)).is_err() {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+pub fn split_name(name: &str) -> (&str, &str) {//split a file name into its stem and extension, keeping the dot with the extension
+    match name.rfind('.') {
+        Some(0) | None => (name, ""),
+        Some(x) => (&name[..x], &name[x..]),
+    }
+}
+pub fn move_into_place(src: &Path, dest: &Path) -> Result<(), FileOpError> {//rename src to exactly dest, falling back to copy and delete across filesystems
+    move_into_place_with(src, dest, None)
+}
+pub fn move_into_place_with(src: &Path, dest: &Path, progress: Option<&JobProgress>) -> Result<(), FileOpError> {//move_into_place, reporting to a background job if there is one
+    check_source(src)?;
+    check_free(dest)?;
+    check_not_inside(src, dest)?;
+    match fs::rename(src, dest) {
+        Ok(..) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_recursive(src, dest, progress) {//the source is untouched, so just drop the partial copy
+                let _ = remove_path(dest);
+                return Err(e);
+            }
+            remove_path(src)
+        }
+        Err(e) => Err(FileOpError::Io(src.to_path_buf(), e)),
+    }
+}
+pub fn create_file(path: &Path) -> Result<(), FileOpError> {//create an empty file, refusing to touch an existing one
+    check_free(path)?;
+    fs::OpenOptions::new().write(true).create_new(true).open(path).map_err(io_err(path))?;
+    Ok(())
+}
+pub fn create_dir(path: &Path) -> Result<(), FileOpError> {//create an empty directory
+    check_free(path)?;
+    fs::create_dir(path).map_err(io_err(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::os::unix::fs::MetadataExt;
+
+    fn scratch_dir(base: &Path, name: &str) -> PathBuf {
+        let dir = base.join(format!("narwhal-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    fn nested_tree(dir: &Path) -> PathBuf {//src/a/b/file plus a relative symlink to it
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("a/b")).unwrap();
+        fs::write(src.join("a/b/file"), "contents").unwrap();
+        symlink("b/file", src.join("a/link")).unwrap();
+        src
+    }
+    fn check_tree(dest: &Path) {
+        assert_eq!(fs::read_to_string(dest.join("a/b/file")).unwrap(), "contents");
+        assert!(fs::symlink_metadata(dest.join("a/link")).unwrap().is_symlink());
+        assert_eq!(fs::read_link(dest.join("a/link")).unwrap(), PathBuf::from("b/file"));
+    }
+
+    #[test]
+    fn copy_keeps_tree_and_symlinks() {
+        let dir = scratch_dir(&env::temp_dir(), "copy");
+        let src = nested_tree(&dir);
+        copy_into_place(&src, &dir.join("dest")).unwrap();
+        check_tree(&dir.join("dest"));
+        check_tree(&src);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn move_across_devices() {
+        let (here, there) = (env::temp_dir(), PathBuf::from("/dev/shm"));
+        match (fs::metadata(&here), fs::metadata(&there)) {
+            (Ok(a), Ok(b)) if a.dev() != b.dev() => {}
+            _ => return,//nothing to cross on this machine
+        }
+        let dir = scratch_dir(&here, "move-from");
+        let other = scratch_dir(&there, "move-to");
+        let src = nested_tree(&dir);
+        move_into_place(&src, &other.join("dest")).unwrap();
+        check_tree(&other.join("dest"));
+        assert!(!src.exists());
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&other).unwrap();
+    }
+    #[test]
+    fn refuses_into_itself() {
+        let dir = scratch_dir(&env::temp_dir(), "itself");
+        let src = nested_tree(&dir);
+        assert!(matches!(copy_into_place(&src, &src.join("a/copy")), Err(FileOpError::IntoItself(..))));
+        assert!(matches!(move_into_place(&src, &src.join("a/moved")), Err(FileOpError::IntoItself(..))));
+        symlink(src.join("a"), dir.join("shortcut")).unwrap();
+        assert!(matches!(copy_into_place(&src, &dir.join("shortcut/copy")), Err(FileOpError::IntoItself(..))));
+        copy_into_place(&src.join("a/b"), &src.join("b")).unwrap();//next to itself is fine
+        check_tree(&src);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn free_names() {
+        let dir = scratch_dir(&env::temp_dir(), "names");
+        assert_eq!(unique_name(&dir, "a.txt"), "a.txt");
+        fs::write(dir.join("a.txt"), "").unwrap();
+        fs::write(dir.join("a (1).txt"), "").unwrap();
+        assert_eq!(unique_name(&dir, "a.txt"), "a (2).txt");
+        assert_eq!(numbered_name(&dir, "New Folder"), "New Folder");
+        fs::create_dir(dir.join("New Folder")).unwrap();
+        assert_eq!(numbered_name(&dir, "New Folder"), "New Folder 2");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn invalid_names() {
+        for name in ["", ".", "..", "a/b", "/"] {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+        assert!(validate_name(".hidden").is_ok());
+    }
+}
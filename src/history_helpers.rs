@@ -0,0 +1,82 @@
+use std::fmt;
+use std::path::PathBuf;
+use gettextrs::gettext as tr;
+use crate::archive_helpers::ArchiveMount;
+
+const HISTORY_LENGTH: usize = 50;
+const RECENT_LENGTH: usize = 10;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Location {//everything needed to come back to a place, the trash and archives included
+    pub path: PathBuf,
+    pub trash: bool,
+    pub archives: Vec<ArchiveMount>,
+}
+#[derive(Clone, Default)]
+pub struct History {//struct representation of the back and forward stacks, the latest entries sit at the end
+    pub back: Vec<Location>,
+    pub forward: Vec<Location>,
+}
+
+impl Location {
+    pub fn shown(&self) -> PathBuf {//the path as the path bar shows it, with the innermost archive's mirror swapped back for the archive
+        match self.archives.last() {
+            Some(mount) => match self.path.strip_prefix(&mount.mirror) {
+                Ok(rest) => mount.shown.join(rest),
+                Err(..) => self.path.clone(),
+            },
+            None => self.path.clone(),
+        }
+    }
+    pub fn title(&self) -> String {//the last component of the shown path, what a tab is labelled with
+        if self.trash {
+            return tr("Trash");
+        }
+        let shown = self.shown();
+        match shown.file_name() {
+            Some(x) => x.to_string_lossy().to_string(),
+            None => shown.to_string_lossy().to_string(),
+        }
+    }
+}
+impl fmt::Display for Location {//what the recent locations dropdown lists
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.trash {
+            true => write!(f, "{}", tr("Trash")),
+            false => write!(f, "{}", self.shown().display()),
+        }
+    }
+}
+impl History {
+    pub fn visit(&mut self, from: Location) {//remember where we are leaving, which makes anything we could go forward to stale
+        if self.back.last() != Some(&from) {
+            self.back.push(from);
+        }
+        if self.back.len() > HISTORY_LENGTH {
+            self.back.remove(0);
+        }
+        self.forward = vec![];
+    }
+    pub fn go_back(&mut self, current: Location) -> Option<Location> {
+        let location = self.back.pop()?;
+        self.forward.push(current);
+        Some(location)
+    }
+    pub fn go_forward(&mut self, current: Location) -> Option<Location> {
+        let location = self.forward.pop()?;
+        self.back.push(current);
+        Some(location)
+    }
+    pub fn recent(&self, current: &Location) -> Vec<Location> {//most recent first, each place listed once and never the one we are in
+        let mut output: Vec<Location> = vec![];
+        for location in self.back.iter().rev().chain(self.forward.iter().rev()) {
+            if location != current && !output.contains(location) {
+                output.push(location.clone());
+            }
+            if output.len() >= RECENT_LENGTH {
+                break;
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,250 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::config_helpers::ConflictPolicy;
+use crate::fs_helpers::{FileOpError, copy_into_place_with, move_into_place_with, unique_name};
+use crate::journal_helpers::JournalOp;
+use crate::trash_helpers::trash_path;
+
+#[derive(Default)]
+pub struct JobProgress {//progress counters shared between a worker thread and the UI
+    pub bytes_done: AtomicU64,
+    pub bytes_total: AtomicU64,
+    pub items_done: AtomicU64,
+    pub items_total: AtomicU64,
+    pub paused: AtomicBool,
+    pub cancelled: AtomicBool,
+    current: Mutex<String>,
+}
+pub struct JobOutcome {//what a finished job hands back to the UI
+    pub ops: Vec<JournalOp>,
+    pub errors: Vec<FileOpError>,
+}
+#[derive(Clone)]
+pub struct TransferItem {//a single source headed for an exact destination, plus what to do if that destination is taken
+    pub source: String,
+    pub dest: PathBuf,
+    pub policy: ConflictPolicy,
+}
+pub struct PendingTransfer {//a transfer waiting on the user to settle its conflicts
+    pub title: String,
+    pub items: Vec<TransferItem>,
+    pub cut: bool,
+    pub background: bool,
+    pub conflicts: Vec<usize>,//indexes into items that still need an answer, asked about in order
+    pub apply_all: bool,
+}
+pub struct Job {//struct representation of a background job
+    pub id: usize,
+    pub title: String,
+    pub progress: Arc<JobProgress>,
+    started: Instant,
+    outcome: Arc<Mutex<Option<JobOutcome>>>,
+}
+
+impl JobProgress {
+    pub fn checkpoint(&self) -> Result<(), FileOpError> {//block while paused, bail out once cancelled
+        while self.paused.load(Ordering::Relaxed) && !self.cancelled.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        match self.cancelled.load(Ordering::Relaxed) {
+            true => Err(FileOpError::Cancelled),
+            false => Ok(()),
+        }
+    }
+    pub fn add_bytes(&self, bytes: u64) {
+        self.bytes_done.fetch_add(bytes, Ordering::Relaxed);
+    }
+    pub fn finish_item(&self) {
+        self.items_done.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn set_current(&self, path: &Path) {
+        if let Ok(mut x) = self.current.lock() {
+            *x = path.to_string_lossy().to_string();
+        }
+    }
+    pub fn current(&self) -> String {
+        match self.current.lock() {
+            Ok(x) => x.clone(),
+            Err(..) => String::new(),
+        }
+    }
+}
+
+pub fn measure(path: &Path) -> (u64, u64) {//total bytes and entries in a tree, without following symlinks
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(x) => x,
+        Err(..) => return (0, 0),
+    };
+    let mut bytes = if metadata.is_file() { metadata.len() } else { 0 };
+    let mut items = 1;
+    if metadata.is_dir() {
+        if let Ok(read_output) = fs::read_dir(path) {
+            for entry in read_output.flatten() {
+                let (b, i) = measure(&entry.path());
+                bytes += b;
+                items += i;
+            }
+        }
+    }
+    (bytes, items)
+}
+
+pub fn is_conflict(source: &Path, dest: &Path) -> bool {//something other than the source itself already sits at dest
+    source != dest && fs::symlink_metadata(dest).is_ok()
+}
+pub fn both_dirs(source: &Path, dest: &Path) -> bool {//merging only makes sense for a real directory on top of a real directory
+    let is_dir = |x: &Path| fs::symlink_metadata(x).map(|m| m.is_dir()).unwrap_or(false);
+    is_dir(source) && is_dir(dest)
+}
+fn place(source: &Path, dest: &Path, cut: bool, progress: Option<&JobProgress>) -> Result<JournalOp, FileOpError> {//copy or move source to a free dest
+    let op = match cut {
+        true => {
+            move_into_place_with(source, dest, progress)?;
+            JournalOp::Move { from: source.to_string_lossy().to_string(), to: dest.to_string_lossy().to_string() }
+        }
+        false => {
+            copy_into_place_with(source, dest, progress)?;
+            JournalOp::Copy { source: source.to_string_lossy().to_string(), created: dest.to_string_lossy().to_string() }
+        }
+    };
+    Ok(op)
+}
+fn transfer(source: &Path, dest: &Path, cut: bool, policy: ConflictPolicy, progress: Option<&JobProgress>, ops: &mut Vec<JournalOp>) -> Result<(), FileOpError> {//copy or move source to dest, settling any conflict according to policy
+    if let Some(x) = progress {
+        x.checkpoint()?;
+    }
+    if dest != source && dest.starts_with(source) {
+        return Err(FileOpError::IntoItself(source.to_path_buf()));
+    }
+    let parent = dest.parent().unwrap_or(Path::new("/"));
+    let name = dest.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+    if source == dest {//pasting a copy next to itself keeps both, moving something onto itself does nothing
+        if !cut {
+            ops.push(place(source, &parent.join(unique_name(parent, &name)), cut, progress)?);
+        }
+        return Ok(());
+    }
+    if !is_conflict(source, dest) {
+        ops.push(place(source, dest, cut, progress)?);
+        return Ok(());
+    }
+    match policy {
+        ConflictPolicy::Skip => Ok(()),
+        ConflictPolicy::KeepBoth => {
+            ops.push(place(source, &parent.join(unique_name(parent, &name)), cut, progress)?);
+            Ok(())
+        }
+        ConflictPolicy::Merge if both_dirs(source, dest) => {
+            for entry in fs::read_dir(source).map_err(|e| FileOpError::Io(source.to_path_buf(), e))? {
+                let entry = entry.map_err(|e| FileOpError::Io(source.to_path_buf(), e))?;
+                transfer(&entry.path(), &dest.join(entry.file_name()), cut, policy, progress, ops)?;
+            }
+            if cut && fs::remove_dir(source).is_ok() {//skipped children keep the source directory alive
+                ops.push(JournalOp::RemoveDir { path: source.to_string_lossy().to_string() });
+            }
+            Ok(())
+        }
+        ConflictPolicy::Ask | ConflictPolicy::Merge => Err(FileOpError::AlreadyExists(dest.to_path_buf())),//files can't be merged, so those are asked about like any other conflict
+        ConflictPolicy::Overwrite => {//whatever is replaced goes to the trash so undo can bring it back
+            if source.starts_with(dest) {//trashing dest would take source along with it
+                return Err(FileOpError::IntoItself(source.to_path_buf()));
+            }
+            let entry = trash_path(dest)?;
+            ops.push(JournalOp::Trash { original: dest.to_string_lossy().to_string(), trashed: entry.files_path.to_string_lossy().to_string(), info: entry.info_path.to_string_lossy().to_string() });
+            ops.push(place(source, dest, cut, progress)?);
+            Ok(())
+        }
+    }
+}
+pub fn run_transfer(items: Vec<TransferItem>, cut: bool, progress: Option<&JobProgress>) -> JobOutcome {//copy or move every item, carrying on past failures
+    let mut sizes = vec![];
+    for item in &items {//measure everything up front so there is a total to report against
+        let (bytes, count) = measure(Path::new(&item.source));
+        if let Some(x) = progress {
+            x.bytes_total.fetch_add(bytes, Ordering::Relaxed);
+            x.items_total.fetch_add(count, Ordering::Relaxed);
+        }
+        sizes.push((bytes, count));
+    }
+    let mut ops = vec![];
+    let mut errors = vec![];
+    let mut bytes_before = 0;
+    let mut items_before = 0;
+    for (item, (bytes, count)) in items.into_iter().zip(sizes) {
+        if let Some(x) = progress {
+            x.set_current(Path::new(&item.source));
+        }
+        match transfer(Path::new(&item.source), &item.dest, cut, item.policy, progress, &mut ops) {
+            Err(FileOpError::Cancelled) => {
+                errors.push(FileOpError::Cancelled);
+                break;
+            }
+            Err(e) => errors.push(e),
+            Ok(..) => {}
+        }
+        bytes_before += bytes;//renames finish without touching a single byte, so settle the counters per source
+        items_before += count;
+        if let Some(x) = progress {
+            x.bytes_done.store(bytes_before, Ordering::Relaxed);
+            x.items_done.store(items_before, Ordering::Relaxed);
+        }
+    }
+    JobOutcome { ops, errors }
+}
+
+impl Job {
+    pub fn spawn<F>(id: usize, title: String, work: F) -> Job where F: FnOnce(&JobProgress) -> JobOutcome + Send + 'static {//run work on its own thread, the UI polls the job for progress
+        let progress = Arc::new(JobProgress::default());
+        let outcome = Arc::new(Mutex::new(None));
+        let thread_progress = progress.clone();
+        let thread_outcome = outcome.clone();
+        thread::spawn(move || {
+            let result = work(&thread_progress);
+            if let Ok(mut x) = thread_outcome.lock() {
+                *x = Some(result);
+            }
+        });
+        Job { id, title, progress, started: Instant::now(), outcome }
+    }
+    pub fn spawn_transfer(id: usize, title: String, items: Vec<TransferItem>, cut: bool) -> Job {//copy or move items in the background
+        Job::spawn(id, title, move |progress| run_transfer(items, cut, Some(progress)))
+    }
+    pub fn take_outcome(&self) -> Option<JobOutcome> {//collect the outcome once the worker is done, this only ever returns it once
+        match self.outcome.lock() {
+            Ok(mut x) => x.take(),
+            Err(..) => None,
+        }
+    }
+    pub fn fraction(&self) -> f32 {
+        let total = self.progress.bytes_total.load(Ordering::Relaxed);
+        match total {
+            0 => 0.0,
+            _ => self.progress.bytes_done.load(Ordering::Relaxed) as f32 / total as f32,
+        }
+    }
+    pub fn throughput(&self) -> f64 {//bytes per second since the job started
+        let elapsed = self.started.elapsed().as_secs_f64();
+        match elapsed > 0.0 {
+            true => self.progress.bytes_done.load(Ordering::Relaxed) as f64 / elapsed,
+            false => 0.0,
+        }
+    }
+    pub fn eta(&self) -> Option<Duration> {
+        let throughput = self.throughput();
+        if throughput <= 0.0 {
+            return None;
+        }
+        let remaining = self.progress.bytes_total.load(Ordering::Relaxed).saturating_sub(self.progress.bytes_done.load(Ordering::Relaxed));
+        Some(Duration::from_secs_f64(remaining as f64 / throughput))
+    }
+    pub fn toggle_pause(&self) {
+        self.progress.paused.fetch_xor(true, Ordering::Relaxed);
+    }
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use serde_derive::{Serialize, Deserialize};
+use crate::archive_helpers::{ArchiveFormat, compress, extract};
+use crate::config_helpers::get_cache_home;
+use crate::fs_helpers::{FileOpError, copy_into_place, create_dir, create_file, move_into_place};
+use crate::trash_helpers::trash_path;
+
+const JOURNAL_LENGTH: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub enum JournalOp {//enum representing a file operation that can be undone
+    Move { from: String, to: String },
+    Copy { source: String, created: String },
+    Create { path: String, is_dir: bool },
+    Trash { original: String, trashed: String, info: String },
+    RemoveDir { path: String },
+    Compress { sources: Vec<String>, created: String },
+    Extract { archive: String, created: String, format: ArchiveFormat, unwrap: bool },
+}
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct PathStamp {//snapshot of a path, used to notice that something touched it after we did
+    pub path: String,
+    pub exists: bool,
+    pub modified_secs: i64,
+    pub modified_nanos: u32,
+    pub size: u64,
+}
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JournalEntry {//a batch of operations plus what their paths looked like right after they last ran or were undone
+    pub ops: Vec<JournalOp>,
+    pub stamps: Vec<PathStamp>,
+}
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Journal {//struct representation of the journal file
+    pub undo: Vec<JournalEntry>,
+    pub redo: Vec<JournalEntry>,
+}
+
+fn journal_home() -> String {
+    format!("{}/NarwhalFM-journal", get_cache_home())
+}
+fn stamp(path: &str) -> PathStamp {//snapshot a path without following symlinks
+    match fs::symlink_metadata(path) {
+        Ok(x) => {
+            let modified = x.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).unwrap_or_default();//toml has no u128, so split seconds and nanoseconds
+            PathStamp { path: path.to_string(), exists: true, modified_secs: modified.as_secs() as i64, modified_nanos: modified.subsec_nanos(), size: x.len() }
+        }
+        Err(..) => PathStamp { path: path.to_string(), exists: false, modified_secs: 0, modified_nanos: 0, size: 0 },
+    }
+}
+fn paths_of(op: &JournalOp) -> Vec<&String> {//every path an operation touches
+    match op {
+        JournalOp::Move { from, to } => vec![from, to],
+        JournalOp::Copy { source: _, created } => vec![created],
+        JournalOp::Create { path, is_dir: _ } => vec![path],
+        JournalOp::Trash { original, trashed, info: _ } => vec![original, trashed],
+        JournalOp::RemoveDir { path } => vec![path],
+        JournalOp::Compress { sources: _, created } => vec![created],
+        JournalOp::Extract { archive: _, created, format: _, unwrap: _ } => vec![created],
+    }
+}
+fn stamps_of(ops: &[JournalOp]) -> Vec<PathStamp> {
+    ops.iter().flat_map(paths_of).map(|x| stamp(x)).collect()
+}
+fn verify(entry: &JournalEntry) -> Result<(), FileOpError> {//refuse to go on if any path changed since we last stamped it
+    for old in &entry.stamps {
+        if stamp(&old.path) != *old {
+            return Err(FileOpError::Changed(PathBuf::from(&old.path)));
+        }
+    }
+    Ok(())
+}
+fn revert(op: &mut JournalOp) -> Result<(), FileOpError> {//apply the inverse of an operation, whole trees go to the trash since stamps can't see edits deep inside them
+    match op {
+        JournalOp::Move { from, to } => move_into_place(Path::new(to), Path::new(from)),
+        JournalOp::Copy { source: _, created } => trash_path(Path::new(created)).map(|_| ()),
+        JournalOp::Create { path, is_dir } => match is_dir {
+            true => fs::remove_dir(path.as_str()).map_err(|e| FileOpError::Io(PathBuf::from(path.as_str()), e)),
+            false => fs::remove_file(path.as_str()).map_err(|e| FileOpError::Io(PathBuf::from(path.as_str()), e)),
+        },
+        JournalOp::Trash { original, trashed, info } => {
+            move_into_place(Path::new(trashed), Path::new(original))?;
+            let _ = fs::remove_file(info.as_str());
+            Ok(())
+        }
+        JournalOp::RemoveDir { path } => create_dir(Path::new(path)),
+        JournalOp::Compress { sources: _, created } => fs::remove_file(created.as_str()).map_err(|e| FileOpError::Io(PathBuf::from(created.as_str()), e)),
+        JournalOp::Extract { archive: _, created, format: _, unwrap: _ } => trash_path(Path::new(created)).map(|_| ()),
+    }
+}
+fn reapply(op: &mut JournalOp) -> Result<(), FileOpError> {//run an operation again after it was undone
+    match op {
+        JournalOp::Move { from, to } => move_into_place(Path::new(from), Path::new(to)),
+        JournalOp::Copy { source, created } => copy_into_place(Path::new(source), Path::new(created)),
+        JournalOp::Create { path, is_dir } => match is_dir {
+            true => create_dir(Path::new(path)),
+            false => create_file(Path::new(path)),
+        },
+        JournalOp::Trash { original, trashed, info } => {
+            let entry = trash_path(Path::new(original))?;
+            *trashed = entry.files_path.to_string_lossy().to_string();
+            *info = entry.info_path.to_string_lossy().to_string();
+            Ok(())
+        }
+        JournalOp::RemoveDir { path } => fs::remove_dir(path.as_str()).map_err(|e| FileOpError::Io(PathBuf::from(path.as_str()), e)),
+        JournalOp::Compress { sources, created } => {
+            let sources: Vec<PathBuf> = sources.iter().map(PathBuf::from).collect();
+            compress(&sources, Path::new(created), None)
+        }
+        JournalOp::Extract { archive, created, format, unwrap } => {//a single unwrapped entry may land under another free name this time
+            let output = extract(Path::new(archive), *format, Path::new(created), *unwrap, None)?;
+            *created = output.to_string_lossy().to_string();
+            Ok(())
+        }
+    }
+}
+
+impl Journal {
+    pub fn load() -> Journal {//collect the journal from the cache directory, starting fresh if it is missing or unreadable
+        match fs::read_to_string(journal_home()) {
+            Ok(x) => toml::from_str(&x).unwrap_or_default(),
+            Err(..) => Journal::default(),
+        }
+    }
+    pub fn save(&self) -> Result<(), FileOpError> {//write the journal out so it survives a restart
+        let path = journal_home();
+        let text = toml::to_string(self).map_err(|e| FileOpError::Io(PathBuf::from(&path), std::io::Error::other(e)))?;
+        fs::write(&path, text).map_err(|e| FileOpError::Io(PathBuf::from(&path), e))
+    }
+    pub fn record(&mut self, ops: Vec<JournalOp>) -> Result<(), FileOpError> {//remember a freshly completed batch of operations, this invalidates anything that could be redone
+        if ops.is_empty() {
+            return Ok(());
+        }
+        let stamps = stamps_of(&ops);
+        self.undo.push(JournalEntry { ops, stamps });
+        if self.undo.len() > JOURNAL_LENGTH {
+            self.undo.remove(0);
+        }
+        self.redo = vec![];
+        self.save()
+    }
+    pub fn undo(&mut self) -> Result<(), FileOpError> {//revert the latest batch, as long as nothing touched its paths since
+        let mut entry = match self.undo.pop() {
+            Some(x) => x,
+            None => return Err(FileOpError::NothingToDo),
+        };
+        if let Err(e) = verify(&entry) {
+            self.undo.push(entry);
+            return Err(e);
+        }
+        let mut result = Ok(());
+        let mut reverted = entry.ops.len();//ops are reverted last to first, everything from here on is done
+        while reverted > 0 {
+            if let Err(e) = revert(&mut entry.ops[reverted - 1]) {
+                result = Err(e);
+                break;
+            }
+            reverted -= 1;
+        }
+        let done = entry.ops.split_off(reverted);//a batch that failed part way is split, so both halves can still be finished or redone
+        if !entry.ops.is_empty() {
+            self.undo.push(JournalEntry { stamps: stamps_of(&entry.ops), ops: entry.ops });
+        }
+        if !done.is_empty() {
+            self.redo.push(JournalEntry { stamps: stamps_of(&done), ops: done });
+        }
+        result.and(self.save())
+    }
+    pub fn redo(&mut self) -> Result<(), FileOpError> {//run the latest undone batch again, as long as nothing touched its paths since
+        let mut entry = match self.redo.pop() {
+            Some(x) => x,
+            None => return Err(FileOpError::NothingToDo),
+        };
+        if let Err(e) = verify(&entry) {
+            self.redo.push(entry);
+            return Err(e);
+        }
+        let mut result = Ok(());
+        let mut applied = 0;//ops are applied first to last, everything before here is done
+        while applied < entry.ops.len() {
+            if let Err(e) = reapply(&mut entry.ops[applied]) {
+                result = Err(e);
+                break;
+            }
+            applied += 1;
+        }
+        let remaining = entry.ops.split_off(applied);
+        if !entry.ops.is_empty() {
+            self.undo.push(JournalEntry { stamps: stamps_of(&entry.ops), ops: entry.ops });
+        }
+        if !remaining.is_empty() {
+            self.redo.push(JournalEntry { stamps: stamps_of(&remaining), ops: remaining });
+        }
+        result.and(self.save())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn undo_copy_trashes_later_edits() {
+        let dir = env::temp_dir().join(format!("narwhal-test-journal-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src/sub")).unwrap();
+        fs::create_dir_all(dir.join("cache")).unwrap();
+        env::set_var("XDG_DATA_HOME", dir.join("data"));//the trash and journal land in here, on the same filesystem as the copy
+        env::set_var("XDG_CACHE_HOME", dir.join("cache"));
+        fs::write(dir.join("src/sub/file"), "original").unwrap();
+        let (source, created) = (dir.join("src"), dir.join("copy"));
+        copy_into_place(&source, &created).unwrap();
+        let mut journal = Journal::default();
+        journal.record(vec![JournalOp::Copy { source: source.to_string_lossy().to_string(), created: created.to_string_lossy().to_string() }]).unwrap();
+        fs::write(created.join("sub/file"), "edited").unwrap();//deep enough that the top folder's stamp doesn't change
+        journal.undo().unwrap();
+        assert!(!created.exists());
+        assert_eq!(fs::read_to_string(dir.join("data/Trash/files/copy/sub/file")).unwrap(), "edited");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,159 +1,106 @@
 use std::path::PathBuf;
 
-use cosmic_time::{chain, Duration};
-use iced::{futures::executor::block_on, widget::text_input, Length};
+use iced::futures::executor::block_on;
+use iced::keyboard::KeyCode;
 
-use crate::{Narwhal, config_helpers::BookmarkDir, sort_file_by_type, RENAME_BTN, TOP_HEIGHT};
+use crate::{Narwhal, config_helpers::{BookmarkDir, ViewMode}};
 
 impl Narwhal {
     pub fn kb_parse(&mut self, kb_event: iced::keyboard::Event) -> iced::Command<<Narwhal as iced::Application>::Message> {
         let mut return_command = iced::Command::none();
         match kb_event {
             iced::keyboard::Event::KeyPressed { key_code, modifiers } => {
+                if self.batch_rename.is_some() || self.properties.is_some() {//the dialog's text fields get the keys, escape backs out of it
+                    if key_code == iced::keyboard::KeyCode::Escape {
+                        self.batch_rename = None;
+                        self.properties = None;
+                    }
+                    return return_command;
+                }
+                if self.archive_prompt.is_some() {//the prompt's text field gets the keys, it confirms on its own
+                    if key_code == iced::keyboard::KeyCode::Escape {
+                        self.archive_prompt = None;
+                    }
+                    return return_command;
+                }
+                if self.location_edit.is_some() {//the location bar's text field gets the keys, tab completes and escape backs out
+                    if key_code == iced::keyboard::KeyCode::Escape {
+                        self.location_edit = None;
+                    } else if key_code == iced::keyboard::KeyCode::Tab {
+                        return_command = self.complete_location();
+                    }
+                    return return_command;
+                }
+                if self.templates.is_some() && self.type_mode.is_none() {//the template menu is a chord, a digit picks a template and anything but a modifier closes it
+                    let digits = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9, KeyCode::Key0];
+                    let modifier_keys = [KeyCode::LShift, KeyCode::RShift, KeyCode::LControl, KeyCode::RControl, KeyCode::LAlt, KeyCode::RAlt];
+                    if let Some(i) = digits.iter().position(|x| *x == key_code) {
+                        return self.create_from_template(i);
+                    }
+                    if !modifier_keys.contains(&key_code) {
+                        self.templates = None;
+                        return return_command;
+                    }
+                }
                 match &self.type_mode {
-                    Some(val) => {
+                    Some(..) => {
                         if key_code == iced::keyboard::KeyCode::Escape {
-                            self.type_mode = None;
-                            use cosmic_time::button;
-                            let un_mitosis = chain![RENAME_BTN,
-                                button(Duration::ZERO).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                                button(Duration::from_millis(500)).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                                button(Duration::from_millis(500)).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                            ];
-                            self.anims.set_chain(un_mitosis).start();
+                            self.finish_rename(false);
                         } else if key_code == iced::keyboard::KeyCode::Enter {
-                            match  self.last_clicked_file {
-                                Some(..) => {
-                                    if val.len() >= 1 {
-                                        self.rename()
-                                    }
-                                    self.type_mode = None;
-                                }
-                                None => {
-                                    self.type_mode = None;
-                                }
-                            }
-                            use cosmic_time::button;
-                            let un_mitosis = chain![RENAME_BTN,
-                                button(Duration::ZERO).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                                button(Duration::from_millis(500)).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                                button(Duration::from_millis(500)).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                            ];
-                            self.anims.set_chain(un_mitosis).start();
+                            self.finish_rename(true);
                         }
                     },
                     None => {
-                if key_code == iced::keyboard::KeyCode::Left {//move the cursor to the left, wrapping around if necessary
-                    let mut old_index = match self.ui_files.len() {
-                        0 => 0,
-                        _ => self.ui_files.len() - 1
-                    };
-                    for i in 0..self.ui_files.len() {
-                        match self.last_clicked_file {
-                            Some(x) => {
-                                if self.ui_files[i].original_index == x && i != 0{
-                                    old_index = i - 1;
-                                    break;
-                                }
-                            },
-                            None => {},
-                        }
-                    }
-                    if self.ui_files.len() > 0 {
-                        let index = self.ui_files[old_index].original_index;
-                        self.last_clicked_file = Some(index);
-                    }
+                if key_code == iced::keyboard::KeyCode::Z && modifiers.control() && modifiers.shift() {//redo the last undone file operation
+                    self.redo();
+                } else if key_code == iced::keyboard::KeyCode::Z && modifiers.control() {//undo the last file operation
+                    self.undo();
+                } else if key_code == iced::keyboard::KeyCode::A && modifiers.control() {//select every visible file
+                    let order = self.visible_indexes();
+                    self.selection.select_all(&order);
+                    self.deletion_confirmation = false;
                     block_on(self.regen_ui_files());
-                } else if key_code == iced::keyboard::KeyCode::Right {//move the cursor to the right, wrapping if necessary
-                    let mut old_index = 0;
-                    for i in 0..self.ui_files.len() {
-                        match self.last_clicked_file {
-                            Some(x) => {
-                                if self.ui_files[i].original_index == x && i != self.ui_files.len() - 1 {
-                                    old_index = i + 1;
-                                    break;
-                                }
-                            },
-                            None => {},
-                        }
-                    }
-                    if self.ui_files.len()>0 {
-                    let index = self.ui_files[old_index].original_index;
-                    self.last_clicked_file = Some(index);
-                    }
-                    block_on(self.regen_ui_files());
-                } else if key_code == iced::keyboard::KeyCode::Down {//move the cursor down, wrap if necessary
-                    let mut old_index = None;
-                    for i in 0..self.ui_files.len() {
-                        match self.last_clicked_file {
-                            Some(x) => {
-                                if self.ui_files[i].original_index == x {
-                                    old_index = Some(i);
-                                    break;
-                                }
-                            },
-                            None => {},
-                        }
-                    }
-                    old_index = match old_index {
-                        Some(x) => {
-                            if self.desired_cols as usize + x < self.ui_files.len() {
-                                Some(x + self.desired_cols as usize)
-                            } else {
-                                Some(x % self.desired_cols as usize)
-                            }
-                        }
-                        None => {
-                            Some(0)
-                        }
-                    };
-                    if self.ui_files.len()>0 {
-                        let index = self.ui_files[old_index.unwrap()].original_index;
-                        self.last_clicked_file = Some(index);
-                    }
-                    block_on(self.regen_ui_files());
-                } else if key_code == iced::keyboard::KeyCode::Up {//move cursor up, wrap if necessary
-                    let mut old_index = None;
-                    for i in 0..self.ui_files.len() {
-                        match self.last_clicked_file {
-                            Some(x) => {
-                                if self.ui_files[i].original_index == x {
-                                    old_index = Some(i);
-                                    break;
-                                }
-                            },
-                            None => {},
-                        }
-                    }
-                    old_index = match old_index {
-                        Some(x) => {
-                            if x >= self.desired_cols as usize {
-                                Some(x - self.desired_cols as usize)
-                            } else {
-                                let length = (self.ui_files.len() - 1) as u32;
-                                let offset = x as u32;
-                                let mut final_value = 0;
-                                for i in 0..self.desired_rows {
-                                    if i*self.desired_cols+offset > length {
-                                        final_value = i-1;
-                                        final_value = final_value*self.desired_cols+offset;
-                                        break;
-                                    }
-                                }
-                                Some(final_value as usize)
-                            }
-                        }
-                        None => {
-                            Some(0)
-                        }
-                    };
-                    if self.ui_files.len() > 0 {
-                        let index = self.ui_files[old_index.unwrap()].original_index;
-                        self.last_clicked_file = Some(index);
-                    }
+                } else if key_code == iced::keyboard::KeyCode::I && modifiers.control() {//invert the selection
+                    let order = self.visible_indexes();
+                    self.selection.invert(&order);
+                    self.deletion_confirmation = false;
                     block_on(self.regen_ui_files());
+                } else if key_code == iced::keyboard::KeyCode::L && modifiers.control() {//type a path into the location bar
+                    return_command = self.open_location_edit();
+                } else if key_code == iced::keyboard::KeyCode::T && modifiers.control() {//open a new tab on the current location
+                    self.open_tab(self.location(), true);
+                } else if key_code == iced::keyboard::KeyCode::W && modifiers.control() {//close the active tab, the last one stays
+                    self.close_tab(self.active_tab);
+                } else if key_code == iced::keyboard::KeyCode::Tab && modifiers.control() {//cycle through the tabs, backwards with shift
+                    self.cycle_tab(!modifiers.shift());
+                } else if key_code == iced::keyboard::KeyCode::Tab {//hand focus to the other pane
+                    self.switch_pane();
+                } else if key_code == iced::keyboard::KeyCode::F3 {//open or close the second pane
+                    self.toggle_split();
+                } else if key_code == iced::keyboard::KeyCode::F5 {//copy the selection into the other pane's folder
+                    self.transfer_across(false);
+                } else if key_code == iced::keyboard::KeyCode::F6 {//move the selection into the other pane's folder
+                    self.transfer_across(true);
+                } else if key_code == iced::keyboard::KeyCode::Left && modifiers.alt() {//go back in the history
+                    self.history_back();
+                } else if key_code == iced::keyboard::KeyCode::Right && modifiers.alt() {//go forward in the history
+                    self.history_forward();
+                } else if self.view_mode == ViewMode::Miller && ((key_code == iced::keyboard::KeyCode::H && !modifiers.shift()) || key_code == iced::keyboard::KeyCode::Left) {//the Miller view moves ranger style, h backs out to the parent and shift+h still toggles hidden files
+                    return_command = self.miller_out();
+                } else if self.view_mode == ViewMode::Miller && (key_code == iced::keyboard::KeyCode::L || key_code == iced::keyboard::KeyCode::Right) {//l steps into the entry under the cursor
+                    return_command = self.miller_in();
+                } else if self.view_mode == ViewMode::Miller && (key_code == iced::keyboard::KeyCode::J || key_code == iced::keyboard::KeyCode::Down) {//j and k move down and up the current folder
+                    return_command = self.miller_step(true, modifiers.shift());
+                } else if self.view_mode == ViewMode::Miller && (key_code == iced::keyboard::KeyCode::K || key_code == iced::keyboard::KeyCode::Up) {
+                    return_command = self.miller_step(false, modifiers.shift());
+                } else if key_code == iced::keyboard::KeyCode::V && modifiers == iced::keyboard::Modifiers::SHIFT {//switch between the grid and the Miller columns
+                    self.toggle_view_mode();
+                } else if [KeyCode::Left, KeyCode::Right, KeyCode::Up, KeyCode::Down, KeyCode::PageUp, KeyCode::PageDown, KeyCode::Home, KeyCode::End].contains(&key_code) {//move the cursor through the grid, scrolling to keep it in view
+                    return_command = self.cursor_step(key_code, modifiers.shift());
+                } else if key_code == iced::keyboard::KeyCode::Enter && modifiers.alt() {//show the properties of the hovered entry
+                    self.open_properties();
                 } else if key_code == iced::keyboard::KeyCode::Enter {//interact with hovered entry
-                    match self.last_clicked_file {
+                    match self.selection.cursor {
                         Some(x) => self.interact_selected_entry(x),
                         None => {}
                     }
@@ -164,20 +111,15 @@ impl Narwhal {
                 } else if key_code == iced::keyboard::KeyCode::S {//cycle sort mode backwards
                     self.change_sort(false);
                 } else if key_code == iced::keyboard::KeyCode::H {//toggle hidden files
-                    self.show_hidden = !self.show_hidden;
-                    block_on(self.regen_ui_files());
-                } else if key_code == iced::keyboard::KeyCode::Minus && modifiers == iced::keyboard::Modifiers::SHIFT {//delete files
-                    match self.last_clicked_file {
-                        Some(x) => {
-                            if self.deletion_confirmation {
-                                self.rm_file(x);
-                            }
-                            self.deletion_confirmation = !self.deletion_confirmation;
-                        }
-                        None => {
-                            self.deletion_confirmation = false;
-                        }
-                    }
+                    self.toggle_hidden();
+                } else if key_code == iced::keyboard::KeyCode::Delete && modifiers.shift() {//permanently delete files, needs a second press to confirm
+                    self.confirm_permanent_delete();
+                } else if (key_code == iced::keyboard::KeyCode::Minus && modifiers == iced::keyboard::Modifiers::SHIFT) || key_code == iced::keyboard::KeyCode::Delete {//trash files
+                    self.delete_selected();
+                } else if key_code == iced::keyboard::KeyCode::T {//open or leave the trash
+                    self.toggle_trash_view();
+                } else if key_code == iced::keyboard::KeyCode::U && self.trash_view {//restore the selected trash entry
+                    self.restore_selected();
                 } else if key_code == iced::keyboard::KeyCode::B && modifiers.shift() {//bookmark or un-bookmark current dir
                     let dir = self.current_path.to_string_lossy().to_string();
                     let paths: Vec<&str> = dir.split('/').into_iter().collect();
@@ -198,113 +140,54 @@ impl Narwhal {
                         }
                     }
                 } else if key_code == iced::keyboard::KeyCode::Key1 && self.bookmarked_dirs.len() > 0 {//activate bookmark dir 1
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[0].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[0].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key2 && self.bookmarked_dirs.len() > 1 {//activate bookmark dir 2
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[1].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[1].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key3 && self.bookmarked_dirs.len() > 2 {//activate bookmark dir 3
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[2].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[2].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key4 && self.bookmarked_dirs.len() > 3 {//activate bookmark dir 4
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[3].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[3].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key5 && self.bookmarked_dirs.len() > 4 {//activate bookmark dir 5
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[4].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[4].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key6 && self.bookmarked_dirs.len() > 5 {//activate bookmark dir 6
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[5].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[5].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key7 && self.bookmarked_dirs.len() > 6 {//activate bookmark dir 7
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[6].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[6].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key8 && self.bookmarked_dirs.len() > 7 {//activate bookmark dir 8
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[7].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[7].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key9 && self.bookmarked_dirs.len() > 8 {//activate bookmark dir 9
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[8].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[8].path.clone()));
                 } else if key_code == iced::keyboard::KeyCode::Key0 && self.bookmarked_dirs.len() > 9 {//activate bookmark dir 10
-                    self.current_path = PathBuf::from(self.bookmarked_dirs[9].path.clone());
-                    self.regen_files();
-                    sort_file_by_type(&mut self.files, self.sort_type.clone());
-                    self.last_clicked_file = None;
-                    block_on(self.regen_ui_files());
-                } else if key_code == iced::keyboard::KeyCode::M {//move files around
-                    match self.mv_target {
-                        Some(..) => {
-                            self.mv_file();
-                        }
-                        None => {
-                            match self.last_clicked_file {
-                                Some(x) => {
-                                    let path = self.files[x].path().to_string_lossy().to_string();
-                                    self.mv_target = Some(path);
-                                }
-                                None => {
-                                    self.mv_target = None;
-                                }
-                            }
-                        }
-                    }
+                    self.change_directory(PathBuf::from(self.bookmarked_dirs[9].path.clone()));
+                } else if key_code == iced::keyboard::KeyCode::X || key_code == iced::keyboard::KeyCode::M {//cut files
+                    self.set_clipboard(true);
+                } else if key_code == iced::keyboard::KeyCode::C && modifiers.shift() {//compress the selection into a new archive
+                    return_command = self.open_compress();
                 } else if key_code == iced::keyboard::KeyCode::C {//copy files
-                    match self.cp_target {
-                        Some(..) => {
-                            self.cp_file();
-                        }
-                        None => {
-                            match self.last_clicked_file {
-                                Some(x) => {
-                                    let path = self.files[x].path().to_string_lossy().to_string();
-                                    self.cp_target = Some(path);
-                                }
-                                None => {
-                                    self.cp_target = None;
-                                }
-                            }
-                        }
-                    }
+                    self.set_clipboard(false);
+                } else if key_code == iced::keyboard::KeyCode::V || key_code == iced::keyboard::KeyCode::P {//paste files into the current directory
+                    self.paste();
+                } else if key_code == iced::keyboard::KeyCode::Escape && self.pending_transfer.is_some() {//back out of a conflict prompt
+                    self.pending_transfer = None;
+                } else if key_code == iced::keyboard::KeyCode::Escape {//forget about whatever was cut or copied
+                    self.clipboard = None;
+                    block_on(self.regen_ui_files());
+                } else if key_code == iced::keyboard::KeyCode::N && modifiers.control() {//open the template menu, a digit then picks one
+                    self.toggle_templates();
                 } else if key_code == iced::keyboard::KeyCode::N && modifiers.shift() {//mkdir
-                    self.mkdir();
+                    return_command = self.mkdir();
                 } else if key_code == iced::keyboard::KeyCode::N {//touch
-                    self.touch();
+                    return_command = self.touch();
+                } else if key_code == iced::keyboard::KeyCode::E && modifiers.shift() {//extract the selected archives somewhere else
+                    return_command = self.open_extract_to();
+                } else if key_code == iced::keyboard::KeyCode::E {//extract the selected archives right here
+                    self.extract_here();
+                } else if key_code == iced::keyboard::KeyCode::R && modifiers.shift() {//bulk rename the selection in $EDITOR
+                    return_command = self.edit_names();
+                } else if key_code == iced::keyboard::KeyCode::R && modifiers.control() {//batch rename the selection
+                    self.open_batch_rename();
                 } else if key_code == iced::keyboard::KeyCode::R {//enter rename mode
-                    self.type_mode = Some(String::default());
-                    use cosmic_time::button;
-                    let mitosis = chain![RENAME_BTN,
-                        button(Duration::ZERO).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                        button(Duration::from_millis(500)).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                        button(Duration::from_millis(500)).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                    ];
-                    self.anims.set_chain(mitosis).start();
-                    return_command = text_input::focus(self.rename_id.clone())
+                    return_command = self.start_rename();
                 }
             }
             }
@@ -312,6 +195,7 @@ impl Narwhal {
             iced::keyboard::Event::KeyReleased { key_code: _, modifiers: _ } => {},
             iced::keyboard::Event::CharacterReceived(_) => {},
             iced::keyboard::Event::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
                 self.show_keybinds = modifiers.control();
             },
         }
@@ -0,0 +1,83 @@
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+pub const COMPLETION_ROWS: usize = 8;//how many matches the dropdown lists at most
+
+pub struct LocationEdit {//the path bar while it is being typed into
+    pub value: String,
+    pub matches: Vec<String>,
+    pub error: Option<String>,
+}
+
+pub fn expand(input: &str) -> String {//~ and $VAR or ${VAR} expanded the way a shell would, unknown variables are left alone
+    let home = env::var("HOME").unwrap_or_default();
+    let text = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{home}{rest}"),
+        _ => input.to_string(),
+    };
+    let mut output = String::new();
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remaining) = match after.strip_prefix('{') {
+            Some(inner) => match inner.find('}') {
+                Some(end) => (&inner[..end], &inner[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        match env::var(name) {
+            Ok(value) if !name.is_empty() => output.push_str(&value),
+            _ => output.push_str(&rest[start..rest.len() - remaining.len()]),
+        }
+        rest = remaining;
+    }
+    output.push_str(rest);
+    output
+}
+pub fn resolve(input: &str, base: &Path) -> PathBuf {//what a typed location points at, relative paths start from base and .. is folded away
+    let mut output = PathBuf::new();
+    for component in base.join(expand(input.trim())).components() {
+        match component {
+            Component::ParentDir => {
+                output.pop();
+            }
+            Component::CurDir => {}
+            x => output.push(x),
+        }
+    }
+    output
+}
+pub fn completions(input: &str, base: &Path) -> Vec<String> {//folders the typed text could be heading for, written out the way the user started typing them
+    let (dir, prefix) = match input.rfind('/') {
+        Some(x) => (&input[..x + 1], &input[x + 1..]),
+        None => ("", input),
+    };
+    let mut output: Vec<String> = match fs::read_dir(base.join(expand(dir))) {
+        Ok(x) => x.flatten()
+            .filter(|x| x.path().is_dir())
+            .map(|x| x.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))//hidden folders only once a dot is typed
+            .map(|name| format!("{dir}{name}/"))
+            .collect(),
+        Err(..) => vec![],
+    };
+    output.sort();
+    output
+}
+pub fn common_prefix(options: &[String]) -> Option<String> {//what tab completion can fill in without having to guess
+    let first = options.first()?;
+    let mut end = first.len();
+    for option in &options[1..] {
+        end = end.min(first.bytes().zip(option.bytes()).take_while(|(a, b)| a == b).count());
+    }
+    while !first.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(first[..end].to_string())
+}
@@ -1,14 +1,16 @@
 #![deny(unsafe_code)]
+use iced::futures::channel::oneshot;
 use iced::futures::executor::block_on;
 use iced::futures::future::join_all;
-use iced::{Application, Result, Settings, executor, Length, Event};
-use iced::widget::{Button, Text, Row, Column, Container, Rule, text_input, TextInput, Space};
+use iced::{Application, Result, Settings, executor, Length, Event, Point};
+use iced::widget::{Button, Text, Row, Column, Container, Rule, text_input, TextInput, Space, ProgressBar, Checkbox, Scrollable, scrollable, svg, PickList};
 use iced::theme;
 use iced_style::Theme;
 use std::collections::HashMap;
 use std::fs::{DirEntry, Metadata};
 use std::{fs, vec};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
 use std::process::Command;
 use toml;
 use gettextrs::*;
@@ -20,6 +22,36 @@ use config_helpers::*;
 mod config_helpers;
 use ui_helpers::*;
 mod ui_helpers;
+use selection_helpers::{FileClipboard, Selection};
+mod selection_helpers;
+use fs_helpers::*;
+mod fs_helpers;
+use journal_helpers::{Journal, JournalOp};
+mod journal_helpers;
+use job_helpers::{Job, JobOutcome, PendingTransfer, TransferItem, both_dirs, is_conflict, run_transfer};
+mod job_helpers;
+use rename_helpers::{BatchField, BatchRename, apply_renames, editor_command, next_case, read_edit_list, run_editor, write_edit_list};
+mod rename_helpers;
+use props_helpers::{PropField, Properties, format_time, group_name, mode_string, user_name};
+mod props_helpers;
+use size_helpers::{SizeCache, SizeScan};
+mod size_helpers;
+use archive_helpers::{ArchiveFormat, ArchiveMount, ArchivePrompt, archive_stem, clear_mirrors, format_for_mime, mirror_path, run_compress, run_extract, run_mirror};
+mod archive_helpers;
+use drag_helpers::{DragState, DropTarget, bookmark_at, moved_far, tile_at};
+mod drag_helpers;
+use history_helpers::{History, Location};
+mod history_helpers;
+use tab_helpers::TabState;
+mod tab_helpers;
+use pane_helpers::{Pane, in_pane, pane_offset};
+mod pane_helpers;
+use miller_helpers::{Preview, entry_label, preview, read_listing};
+mod miller_helpers;
+use location_helpers::{COMPLETION_ROWS, LocationEdit, common_prefix, completions, resolve};
+mod location_helpers;
+use trash_helpers::{TrashEntry, all_trash_dirs, delete_entry, empty_trash, list_trash, restore_entry, trash_path};
+mod trash_helpers;
 mod kb_parser;
 mod default_state;
 use cosmic_time::{
@@ -46,8 +78,9 @@ const SIDEBAR_WIDTH: u16 = 120;
 const IMAGE_SCALE: u16 = 64;
 const RULE_WIDTH: u16 = 1;
 const TOP_HEIGHT: u16 = 30;
+const DROP_SETTLE: std::time::Duration = std::time::Duration::from_millis(150);//files of one drop arrive as separate events, wait this long after the last one
+const GRID_MARGIN: usize = 2;//rows of tiles built above and below the ones in view
 
-static RENAME_BTN: Lazy<id::Button> = Lazy::new(id::Button::unique);
 static MENU_BTN: Lazy<id::Button> = Lazy::new(id::Button::unique);
 static MENU_BTN_LONG: Lazy<id::Button> = Lazy::new(id::Button::unique);
 
@@ -58,13 +91,13 @@ struct Narwhal {//contains all application state
     desired_cols: u32,
     desired_rows: u32,
     show_hidden: bool,
-    last_clicked_file: Option<usize>,
+    selection: Selection,
+    modifiers: iced::keyboard::Modifiers,
     ui_files: Vec<UIFile>,
     icon_cache: HashMap<String, String>,
     bookmarked_dirs: Vec<BookmarkDir>,
     deletion_confirmation: bool,
-    mv_target: Option<String>,
-    cp_target: Option<String>,
+    clipboard: Option<FileClipboard>,
     themes: ThemeSet,
     theme: SelectedTheme,
     type_mode: Option<String>,
@@ -74,12 +107,75 @@ struct Narwhal {//contains all application state
     icn_theme: String,
     icn_size: u16,
     show_file_options: bool,
+    op_error: Option<String>,
+    trash_view: bool,
+    trash_entries: HashMap<PathBuf, TrashEntry>,
+    empty_confirmation: bool,
+    journal: Journal,
+    jobs: Vec<Job>,
+    next_job_id: usize,
+    conflict_policy: ConflictPolicy,
+    pending_transfer: Option<PendingTransfer>,
+    batch_rename: Option<BatchRename>,
+    editor_rename: Option<Vec<PathBuf>>,
+    templates: Option<Vec<(PathBuf, String)>>,
+    properties: Option<Properties>,
+    size_scan: Option<SizeScan>,
+    size_cache: SizeCache,
+    archive_prompt: Option<ArchivePrompt>,
+    archive_stack: Vec<ArchiveMount>,
+    opening_archive: Option<(usize, Location, ArchiveMount)>,//the job unpacking an archive, and where we were when it was opened
+    cursor_position: Option<Point>,
+    drop_hover: Vec<PathBuf>,
+    dropped: Vec<PathBuf>,
+    dropped_at: Option<std::time::Instant>,//when the latest dropped file came in
+    drop_target: Option<DropTarget>,
+    drag: Option<DragState>,
+    history: History,
+    prompt_id: text_input::Id,
+    tabs: Vec<TabState>,
+    active_tab: usize,
+    window_cols: u32,
+    split: Option<Pane>,
+    focus_right: bool,
+    view_mode: ViewMode,
+    parent_files: Vec<DirEntry>,
+    miller_key: Option<(Option<PathBuf>, Option<PathBuf>, String)>,//parent, cursor and sort the Miller columns were last built for
+    preview: Option<Preview>,
+    miller_id: scrollable::Id,
+    grid_id: scrollable::Id,
+    grid_offset: f32,
+    grid_height: f32,
+    ui_start_row: usize,
+    ui_end_row: usize,
+    location_edit: Option<LocationEdit>,
+    location_id: text_input::Id,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {//enum representing button events
-    FileClicked(usize),
+    DragStart(usize),
+    DragReleased,
     GoBack(usize),
+    HistoryBack,
+    HistoryForward,
+    HistoryChosen(Location),
+    NewTab,
+    TabSelected(usize),
+    TabClosed(usize),
+    OpenInNewTab(usize),
+    SplitToggled,
+    PaneFocused,
+    CopyAcross,
+    MoveAcross,
+    ViewToggled,
+    MillerClicked(usize),
+    MillerParent(PathBuf),
+    GridScrolled(scrollable::Viewport),
+    LocationEditOpen,
+    LocationEdited(String),
+    LocationSubmitted,
+    LocationChosen(String),
     SortChanged,
     HiddenChanged,
     KeyboardUpdate(iced::keyboard::Event),
@@ -87,12 +183,48 @@ pub enum Message {//enum representing button events
     BookmarkCurrent,
     BookmarkClicked(usize),
     DeleteClicked,
-    MvClicked,
-    CpClicked,
+    PermDeleteClicked,
+    TrashClicked,
+    RestoreClicked,
+    EmptyTrashClicked,
+    CutClicked,
+    CopyClicked,
+    PasteClicked,
+    ClearClipboard,
+    JobTick,
+    PauseJob(usize),
+    CancelJob(usize),
+    ResolveConflict(ConflictPolicy),
+    ApplyToAllToggled(bool),
+    CancelTransfer,
     MkFile,
     MkDir,
+    TemplateMenuToggle,
+    TemplateChosen(usize),
     RenameToggle,
     RenameUpdate(String),
+    BatchRenameOpen,
+    BatchRenameEdit(BatchField, String),
+    BatchRenameRegex(bool),
+    BatchRenameKeepExt(bool),
+    BatchRenameCase,
+    BatchRenameApply,
+    BatchRenameCancel,
+    EditorClosed(std::result::Result<(), String>),
+    PropertiesOpen,
+    PropertiesEdit(PropField, String),
+    PropertiesBit(u32, bool),
+    PropertiesRecursive(bool),
+    PropertiesApply,
+    PropertiesClose,
+    CompressClicked,
+    ExtractHere,
+    ExtractTo,
+    ArchivePromptUpdate(String),
+    ArchivePromptConfirm,
+    ArchivePromptCancel,
+    CursorMoved(Point),
+    DropFinished,
     Tick(Instant),
     ToggleMenu,
     NoOp,
@@ -147,35 +279,36 @@ fn folder_cmp(a: &DirEntry, b: &DirEntry, folders_first: bool) -> std::cmp::Orde
 }
 
 impl Narwhal {
-    async fn regen_ui_files(&mut self) {
-        let mut items_flushed = 0;
-        let max_iter = self.desired_cols * self.desired_rows;
+    async fn regen_ui_files(&mut self) {//build tiles for the rows in view plus a margin either side, everything else is just empty space in the scrollable
+        let cols = self.desired_cols as usize;
+        let order = self.visible_indexes();
+        let total_height = ((order.len() + cols - 1) / cols * EST_HEIGHT as usize) as f32;
+        self.grid_offset = self.grid_offset.min((total_height - self.grid_height).max(0.0));//the listing may have shrunk since the last scroll
+        let first_row = (self.grid_offset / EST_HEIGHT as f32) as usize;
+        self.ui_start_row = first_row.saturating_sub(GRID_MARGIN);
+        self.ui_end_row = first_row + self.desired_rows as usize + 1 + GRID_MARGIN;
+        let max_iter = ((self.ui_end_row - self.ui_start_row) * cols) as u32;
         let mut futures = Vec::with_capacity(max_iter as usize);
         let mut names = Vec::with_capacity(max_iter as usize);
         let mut selected_vals = Vec::with_capacity(max_iter as usize);
+        let mut dimmed_vals = Vec::with_capacity(max_iter as usize);
         let mut original_indexes = Vec::with_capacity(max_iter as usize);
         let mut all_changes = vec![];
         let exec = iced::executor::Default::new().unwrap();
         self.ui_files = Vec::with_capacity(max_iter as usize);
-        for i in 0..self.files.len() {
-            if items_flushed >= max_iter {
-                break;
-            }
+        for i in order.into_iter().take(self.ui_end_row * cols).skip(self.ui_start_row * cols) {
             let name = self.files[i].file_name().to_string_lossy().to_string();
-            let chars: Vec<char> = name.chars().collect();
-            if !self.show_hidden && chars[0] == '.' {//filter out hidden files if desired
-            } else {
-                let path = self.files[i].path().to_string_lossy().to_string();
-                let selected = match self.last_clicked_file {
-                    Some(value) => value == i,
-                    None => false
-                };
-                futures.push(exec.spawn(get_file_icon(self.icon_cache.clone(), path.clone(), self.icn_theme.clone(), self.icn_size)));//spawn all file icon fetching futures
-                names.push(name);
-                selected_vals.push(selected);
-                original_indexes.push(i);
-                items_flushed = items_flushed + 1;
-            }
+            let path = self.files[i].path().to_string_lossy().to_string();
+            let selected = self.selection.contains(i) || self.selection.cursor == Some(i);
+            let dimmed = match &self.clipboard {//cut files fade out until they get pasted
+                Some(clipboard) => clipboard.cut && clipboard.contains(&path),
+                None => false
+            };
+            futures.push(exec.spawn(get_file_icon(self.icon_cache.clone(), path.clone(), self.icn_theme.clone(), self.icn_size)));//spawn all file icon fetching futures
+            names.push(name);
+            selected_vals.push(selected);
+            dimmed_vals.push(dimmed);
+            original_indexes.push(i);
         }
         for i in 0..futures.len() {//resolve futures
             let output = futures.remove(0).await.unwrap();//wait for it to finish then collect result
@@ -188,26 +321,91 @@ impl Narwhal {
 
                 }
             }
-            let ui_file = UIFile { name: names[i].clone(), original_index: original_indexes[i], selected: selected_vals[i], icon };//construct the UIFile and push it onto the vec
+            let ui_file = UIFile { name: names[i].clone(), original_index: original_indexes[i], selected: selected_vals[i], dimmed: dimmed_vals[i], targeted: self.drop_target == Some(DropTarget::Tile(original_indexes[i])), icon };//construct the UIFile and push it onto the vec
             self.ui_files.push(ui_file);
         }
         for change in all_changes {//for every change, push it onto the cache
             self.icon_cache.extend(change.into_iter());
         }
         self.type_mode = None;
+        self.update_size_scan();
+        self.update_miller();
     }
     fn regen_files(&mut self) {//rebuild file list
         self.files = vec![];
+        if self.trash_view {//the trash is a virtual location made of every trash directory's files
+            self.trash_entries = HashMap::new();
+            for entry in list_trash() {
+                self.trash_entries.insert(entry.files_path.clone(), entry);
+            }
+            for trash in all_trash_dirs() {
+                if let Ok(read_output) = fs::read_dir(trash.join("files")) {
+                    for path in read_output.flatten() {
+                        if self.trash_entries.contains_key(&path.path()) {
+                            self.files.push(path);
+                        }
+                    }
+                }
+            }
+            return;
+        }
         let read_output = match fs::read_dir(self.current_path.clone()) {
             Ok(x) => x,
-            Err(x) => panic!("{}", x),
+            Err(x) => {//leave the listing empty and say why, rather than taking the whole window down
+                self.op_error = Some(format!("{}: {}", self.current_path.display(), x));
+                return;
+            }
         };
-        for path in read_output {
-            self.files.push(path.unwrap())
+        for path in read_output.flatten() {
+            self.files.push(path)
+        }
+    }
+    fn visible_indexes(&self) -> Vec<usize> {//indexes into files of every entry that is not filtered out, in display order
+        let mut indexes = vec![];
+        for i in 0..self.files.len() {
+            let name = self.files[i].file_name().to_string_lossy().to_string();
+            if self.show_hidden || !name.starts_with('.') {
+                indexes.push(i);
+            }
+        }
+        indexes
+    }
+    fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        let visible = self.visible_indexes();
+        self.selection.retain(&visible);
+        self.deletion_confirmation = false;
+        block_on(self.regen_ui_files());
+    }
+    fn click_file(&mut self, index: usize) {//ctrl toggles, shift selects a range, anything else selects or opens
+        self.deletion_confirmation = false;//an armed permanent delete only ever applies to what was selected when it was armed
+        if self.modifiers.control() {
+            self.selection.toggle(index);
+            block_on(self.regen_ui_files());
+        } else if self.modifiers.shift() {
+            let order = self.visible_indexes();
+            self.selection.extend_to(index, &order);
+            block_on(self.regen_ui_files());
+        } else {
+            self.interact_selected_entry(index);
+        }
+    }
+    fn move_cursor(&mut self, index: usize, extend: bool) {//keyboard cursor movement, extending the selection when shift is held
+        self.deletion_confirmation = false;
+        if extend {
+            let order = self.visible_indexes();
+            self.selection.extend_to(index, &order);
+        } else {
+            self.selection.select_only(index);
         }
     }
     fn interact_selected_entry(&mut self, index: usize) {//do sanity checks and then interact with the currently hovered entry if all checks pass
-        match self.last_clicked_file {
+        if self.trash_view {//trashed entries can only be selected, not opened
+            self.selection.select_only(index);
+            block_on(self.regen_ui_files());
+            return;
+        }
+        match self.selection.cursor {
             Some(x) => {
                 if x == index {
                     let metadata = self.files[x].metadata().unwrap();
@@ -215,6 +413,7 @@ impl Narwhal {
                         let mime = get_file_mimetype(self.files[x].path().to_string_lossy().to_string()).replace("/", "-");
                         let path = fs::read_link(self.files[x].path().to_string_lossy().to_string()).unwrap();
                         if mime == "inode-directory" {
+                            self.remember_location();
                             let path_txt = path.to_string_lossy().to_string();
                             let path_chars: Vec<char> = path_txt.chars().collect();
                             let clean_path = if path_chars[0] == '/' {
@@ -232,33 +431,509 @@ impl Narwhal {
                     } else {
                         let mime = get_file_mimetype(self.files[x].path().to_string_lossy().to_string()).replace("/", "-");
                         if mime == "inode-directory" {
+                            self.remember_location();
                             let filename = self.files[x].path().display().to_string();
                             self.current_path.push(filename);
                             println!("{}", self.current_path.to_string_lossy());
                             self.regen_files();
                             sort_file_by_type(&mut self.files, self.sort_type.clone());
-                        } else {
+                        } else if !self.open_archive(&self.files[x].path()) {
                             let filename = self.files[x].path().display().to_string();
                             Command::new("xdg-open").arg(filename).spawn().expect("oops");
                         }
                     }
-                    self.last_clicked_file = None;
+                    self.selection.clear();
                 } else {
-                    self.last_clicked_file = Some(index);
+                    self.selection.select_only(index);
                 }
                 block_on(self.regen_ui_files());
             }
             None => {
-                self.last_clicked_file = Some(index);
+                self.selection.select_only(index);
                 block_on(self.regen_ui_files());
             }
         }
     }
-    fn go_back_directory(&mut self) {//pop an entry off of the current path, regenerate the file list and UI Files
-        self.current_path.pop();
+    fn change_directory(&mut self, path: PathBuf) {//jump to a new directory, leaving the trash or any archive if we were in one
+        self.remember_location();
+        self.current_path = path;
+        self.trash_view = false;
+        self.archive_stack.clear();
+        self.deletion_confirmation = false;
+        self.empty_confirmation = false;
+        self.refresh_after_op();
+    }
+    fn open_archive(&mut self, path: &Path) -> bool {//step into an archive as if it was a folder, false if it isn't one we can read
+        let name = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        let format = match format_for_mime(&get_file_mimetype(path.to_string_lossy().to_string()), &name) {
+            Some(x) => x,
+            None => return false,
+        };
+        let mirror = match self.report(mirror_path(path)) {
+            Some(x) => x,
+            None => return true,
+        };
+        let mount = ArchiveMount { archive: path.to_path_buf(), shown: self.shown_path().join(&name), mirror: mirror.clone() };
+        if mirror.is_dir() {//unpacked earlier this session and unchanged since
+            self.enter_archive(mount);
+            return true;
+        }
+        let title = format!("{} {}", tr("Opening"), name);
+        let archive = path.to_path_buf();
+        self.jobs.push(Job::spawn(self.next_job_id, title, move |progress| run_mirror(archive, format, mirror, Some(progress))));
+        self.opening_archive = Some((self.next_job_id, self.location(), mount));
+        self.next_job_id += 1;
+        true
+    }
+    fn enter_archive(&mut self, mount: ArchiveMount) {
+        self.remember_location();
+        self.current_path = mount.mirror.clone();
+        self.archive_stack.push(mount);
+        self.selection.clear();
+        self.regen_files();
+        sort_file_by_type(&mut self.files, self.sort_type.clone());
+    }
+    fn shown_path(&self) -> PathBuf {//the current path with the innermost archive's mirror swapped back for the archive
+        self.location().shown()
+    }
+    fn location(&self) -> Location {
+        Location { path: self.current_path.clone(), trash: self.trash_view, archives: self.archive_stack.clone() }
+    }
+    fn remember_location(&mut self) {//call right before leaving for somewhere else
+        let here = self.location();
+        self.history.visit(here);
+    }
+    fn go_to(&mut self, location: Location) {//return to a remembered place, unless it has gone away in the meantime
+        if !location.trash && !location.path.is_dir() {
+            self.op_error = Some(format!("{} {}", location.shown().display(), tr("no longer exists")));
+            return;
+        }
+        self.current_path = location.path;
+        self.archive_stack = location.archives;
+        self.show_trash(location.trash);
+    }
+    fn history_back(&mut self) {
+        let here = self.location();
+        if let Some(location) = self.history.go_back(here) {
+            self.go_to(location);
+        }
+    }
+    fn history_forward(&mut self) {
+        let here = self.location();
+        if let Some(location) = self.history.go_forward(here) {
+            self.go_to(location);
+        }
+    }
+    fn snapshot_tab(&self) -> TabState {//the active tab as it stands right now
+        TabState {
+            current_path: self.current_path.clone(),
+            sort_type: self.sort_type.clone(),
+            show_hidden: self.show_hidden,
+            trash_view: self.trash_view,
+            archive_stack: self.archive_stack.clone(),
+            selected: self.selected_paths(),
+            cursor: self.selection.cursor.and_then(|x| self.files.get(x)).map(|x| x.path()),
+            history: self.history.clone(),
+        }
+    }
+    fn load_tab(&mut self, tab: TabState) {//make a tab the live one, anything left half done in the tab being left is dropped
+        self.current_path = tab.current_path;
+        self.sort_type = tab.sort_type;
+        self.show_hidden = tab.show_hidden;
+        self.trash_view = tab.trash_view;
+        self.archive_stack = tab.archive_stack;
+        self.history = tab.history;
+        self.batch_rename = None;
+        self.properties = None;
+        self.templates = None;
+        self.archive_prompt = None;
+        self.drag = None;
+        self.deletion_confirmation = false;
+        self.empty_confirmation = false;
+        self.reload_keeping(&tab.selected, tab.cursor);
+        block_on(self.regen_ui_files());
+    }
+    fn reload_keeping(&mut self, selected: &[PathBuf], cursor: Option<PathBuf>) {//re-read the directory, selecting the same paths again afterwards
+        if !self.trash_view && !self.current_path.is_dir() {//the folder went away while nobody was looking, fall back on the closest one left
+            self.archive_stack.clear();
+            while !self.current_path.is_dir() && self.current_path.pop() {}
+        }
+        self.regen_files();
+        sort_file_by_type(&mut self.files, self.sort_type.clone());
+        let items: Vec<usize> = selected.iter().filter_map(|path| self.files.iter().position(|x| x.path() == *path)).collect();
+        let cursor = cursor.and_then(|path| self.files.iter().position(|x| x.path() == path));
+        self.selection.restore(items, cursor);
+    }
+    fn open_tab(&mut self, location: Location, switch: bool) {//add a tab right after the active one, it starts with the active tab's sort and hidden settings
+        let tab = TabState::new(location, self.sort_type.clone(), self.show_hidden);
+        self.tabs.insert(self.active_tab + 1, tab);
+        if switch {
+            self.switch_tab(self.active_tab + 1);
+        }
+    }
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        self.tabs[self.active_tab] = self.snapshot_tab();
+        self.active_tab = index;
+        self.load_tab(self.tabs[index].clone());
+    }
+    fn close_tab(&mut self, index: usize) {//the last tab stays open
+        if self.tabs.len() < 2 || index >= self.tabs.len() {
+            return;
+        }
+        self.tabs.remove(index);
+        if index < self.active_tab {
+            self.active_tab -= 1;
+        } else if index == self.active_tab {//the tab that slides into its place takes over, or the one before if it was the last
+            self.active_tab = index.min(self.tabs.len() - 1);
+            self.load_tab(self.tabs[self.active_tab].clone());
+        }
+    }
+    fn cycle_tab(&mut self, forward: bool) {
+        let len = self.tabs.len();
+        let index = match forward {
+            true => (self.active_tab + 1) % len,
+            false => (self.active_tab + len - 1) % len,
+        };
+        self.switch_tab(index);
+    }
+    fn open_in_new_tab(&mut self, index: usize) {//middle click on a folder opens it in a background tab, archive members keep their archive
+        if self.trash_view || index >= self.files.len() || !self.files[index].path().is_dir() {
+            return;
+        }
+        let location = Location { path: self.files[index].path(), trash: false, archives: self.archive_stack.clone() };
+        self.open_tab(location, false);
+    }
+    fn saved_tabs(&mut self) -> Vec<SavedTab> {//what gets written to the config so the tabs come back next launch
+        self.tabs[self.active_tab] = self.snapshot_tab();
+        self.tabs.iter().map(|x| SavedTab { path: x.saved_path().to_string_lossy().to_string(), sort_mode: encode_sort(x.sort_type.clone()), show_hidden: x.show_hidden }).collect()
+    }
+    fn fit_panes(&mut self) {//the window's columns go to one pane, or get shared out between two
+        self.desired_cols = match self.split {
+            Some(..) => self.window_cols / 2,
+            None => self.window_cols,
+        };
+        if self.desired_cols == 0 {
+            self.desired_cols = 1;
+        }
+    }
+    fn swap_panes(&mut self) {//trade everything that belongs to the focused pane with the other pane
+        if let Some(pane) = &mut self.split {
+            std::mem::swap(&mut self.files, &mut pane.files);
+            std::mem::swap(&mut self.ui_files, &mut pane.ui_files);
+            std::mem::swap(&mut self.current_path, &mut pane.current_path);
+            std::mem::swap(&mut self.sort_type, &mut pane.sort_type);
+            std::mem::swap(&mut self.show_hidden, &mut pane.show_hidden);
+            std::mem::swap(&mut self.trash_view, &mut pane.trash_view);
+            std::mem::swap(&mut self.trash_entries, &mut pane.trash_entries);
+            std::mem::swap(&mut self.archive_stack, &mut pane.archive_stack);
+            std::mem::swap(&mut self.selection, &mut pane.selection);
+            std::mem::swap(&mut self.history, &mut pane.history);
+            std::mem::swap(&mut self.grid_offset, &mut pane.grid_offset);
+            std::mem::swap(&mut self.ui_start_row, &mut pane.ui_start_row);
+            std::mem::swap(&mut self.ui_end_row, &mut pane.ui_end_row);
+        }
+    }
+    fn toggle_split(&mut self) {//open a second pane on the current folder, or close the one without focus
+        self.split = match self.split {
+            Some(..) => None,
+            None => Some(Pane::new(self.current_path.clone(), self.sort_type.clone(), self.show_hidden)),
+        };
+        self.focus_right = false;
+        self.fit_panes();
+        block_on(self.regen_ui_files());
+        self.refresh_other_pane();
+    }
+    fn switch_pane(&mut self) {//hand focus to the other pane, anything half done in this one is dropped
+        if self.split.is_none() {
+            return;
+        }
+        self.swap_panes();
+        self.focus_right = !self.focus_right;
+        self.type_mode = None;
+        self.batch_rename = None;
+        self.properties = None;
+        self.archive_prompt = None;
+        self.drag = None;
+        self.deletion_confirmation = false;
+        self.empty_confirmation = false;
+        self.update_size_scan();
+        self.update_miller();
+    }
+    fn refresh_other_pane(&mut self) {//re-read the pane without focus, an operation in one pane may well have changed what the other shows
+        if self.split.is_none() {
+            return;
+        }
+        let type_mode = self.type_mode.take();
+        self.swap_panes();
+        let selected = self.selected_paths();
+        let cursor = self.selection.cursor.and_then(|x| self.files.get(x)).map(|x| x.path());
+        self.reload_keeping(&selected, cursor);
+        block_on(self.regen_ui_files());
+        self.swap_panes();
+        self.type_mode = type_mode;
+        self.update_size_scan();
+        self.update_miller();
+    }
+    fn toggle_view_mode(&mut self) {
+        self.view_mode = match self.view_mode {
+            ViewMode::Grid => ViewMode::Miller,
+            ViewMode::Miller => ViewMode::Grid,
+        };
+        self.drop_target = None;
+        self.miller_key = None;
+        self.update_miller();
+    }
+    fn update_miller(&mut self) {//list the parent folder and preview the entry under the cursor, only the Miller view shows them
+        if self.view_mode != ViewMode::Miller {
+            return;
+        }
+        let parent = match self.archive_stack.last() {//the top of an archive lists the folder holding the archive, the trash has nothing above it
+            _ if self.trash_view => None,
+            Some(mount) if mount.mirror == self.current_path => mount.archive.parent(),
+            _ => self.current_path.parent(),
+        };
+        let cursor = self.selection.cursor.and_then(|x| self.files.get(x)).map(|x| x.path());
+        let key = (parent.map(|x| x.to_path_buf()), cursor.clone(), encode_sort(self.sort_type.clone()));
+        if self.miller_key.as_ref() == Some(&key) {//regens come with every scroll and keypress, only go to disk once something shown changed
+            return;
+        }
+        if self.miller_key.as_ref().map(|x| x.0 != key.0 || x.2 != key.2).unwrap_or(true) {
+            self.parent_files = match parent {
+                Some(x) => read_listing(x, self.sort_type.clone()),
+                None => vec![],
+            };
+        }
+        self.preview = cursor.map(|x| preview(&x, self.sort_type.clone()));
+        self.miller_key = Some(key);
+    }
+    fn scroll_to_cursor(&mut self) -> iced::Command<Message> {//scroll just far enough for the cursor's row to be in full view
+        if self.view_mode == ViewMode::Miller {
+            return self.miller_scroll();
+        }
+        let order = self.visible_indexes();
+        let position = match self.selection.cursor.and_then(|x| order.iter().position(|y| *y == x)) {
+            Some(x) => x,
+            None => return iced::Command::none(),
+        };
+        let top = (position / self.desired_cols as usize * EST_HEIGHT as usize) as f32;
+        let bottom = top + EST_HEIGHT as f32;
+        if top < self.grid_offset {
+            self.grid_offset = top;
+        } else if bottom > self.grid_offset + self.grid_height {
+            self.grid_offset = (bottom - self.grid_height).max(0.0);
+        } else {
+            return iced::Command::none();
+        }
+        scrollable::scroll_to(self.grid_id.clone(), scrollable::AbsoluteOffset { x: 0.0, y: self.grid_offset })
+    }
+    fn cursor_to(&mut self, position: usize, extend: bool) -> iced::Command<Message> {//move the cursor to a position in display order and bring it into view
+        let order = self.visible_indexes();
+        if position >= order.len() {
+            return iced::Command::none();
+        }
+        self.move_cursor(order[position], extend);
+        let command = self.scroll_to_cursor();
+        block_on(self.regen_ui_files());
+        command
+    }
+    fn cursor_step(&mut self, key_code: iced::keyboard::KeyCode, extend: bool) -> iced::Command<Message> {//arrow keys and paging in the grid, in display order so entries outside the built tiles can be reached too
+        let order = self.visible_indexes();
+        if order.is_empty() {
+            return iced::Command::none();
+        }
+        let last = order.len() - 1;
+        let cols = self.desired_cols as usize;
+        let page = (self.grid_height / EST_HEIGHT as f32).max(1.0) as usize * cols;
+        let current = self.selection.cursor.and_then(|x| order.iter().position(|y| *y == x));
+        let position = match (key_code, current) {
+            (iced::keyboard::KeyCode::Left, Some(x)) if x > 0 => x - 1,//left and right wrap around the ends
+            (iced::keyboard::KeyCode::Left, _) => last,
+            (iced::keyboard::KeyCode::Right, Some(x)) if x < last => x + 1,
+            (iced::keyboard::KeyCode::Down, Some(x)) if x + cols <= last => x + cols,
+            (iced::keyboard::KeyCode::Down, Some(x)) => x % cols,//up and down wrap within the column
+            (iced::keyboard::KeyCode::Up, Some(x)) if x >= cols => x - cols,
+            (iced::keyboard::KeyCode::Up, Some(x)) => x + (last - x) / cols * cols,
+            (iced::keyboard::KeyCode::PageDown, Some(x)) => (x + page).min(last),
+            (iced::keyboard::KeyCode::PageUp, Some(x)) => x.saturating_sub(page),
+            (iced::keyboard::KeyCode::End, _) => last,
+            _ => 0,
+        };
+        self.cursor_to(position, extend)
+    }
+    fn miller_scroll(&self) -> iced::Command<Message> {//keep the cursor in sight in the middle column
+        let order = self.visible_indexes();
+        let position = self.selection.cursor.and_then(|x| order.iter().position(|y| *y == x)).unwrap_or(0);
+        let y = match order.len() {
+            0 | 1 => 0.0,
+            n => position as f32 / (n - 1) as f32,
+        };
+        scrollable::snap_to(self.miller_id.clone(), scrollable::RelativeOffset { x: 0.0, y })
+    }
+    fn miller_step(&mut self, forward: bool, extend: bool) -> iced::Command<Message> {//j and k, move the cursor down or up the middle column without wrapping
+        let order = self.visible_indexes();
+        if order.is_empty() {
+            return iced::Command::none();
+        }
+        let position = match self.selection.cursor.and_then(|x| order.iter().position(|y| *y == x)) {
+            Some(x) if forward => (x + 1).min(order.len() - 1),
+            Some(x) => x.saturating_sub(1),
+            None => 0,
+        };
+        self.cursor_to(position, extend)
+    }
+    fn miller_out(&mut self) -> iced::Command<Message> {//h, back out to the parent with the folder we left under the cursor
+        let from = self.current_path.clone();
+        let archive = self.archive_stack.last().map(|x| x.archive.clone());
+        self.go_back_directory();
+        if let Some(x) = self.files.iter().position(|x| x.path() == from || Some(x.path()) == archive) {
+            self.selection.select_only(x);
+            block_on(self.regen_ui_files());
+        }
+        self.miller_scroll()
+    }
+    fn miller_in(&mut self) -> iced::Command<Message> {//l, step into the folder under the cursor, or open the file
+        if let Some(x) = self.selection.cursor {
+            self.interact_selected_entry(x);
+        }
+        self.miller_scroll()
+    }
+    fn miller_columns(&self) -> iced::Element<'_, Message, iced::Renderer<Theme>> {//parent folder, current folder and a preview of the entry under the cursor
+        let mut parent_column = Column::new();
+        for entry in &self.parent_files {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !self.show_hidden && name.starts_with('.') {
+                continue;
+            }
+            let here = entry.path() == self.current_path || self.archive_stack.last().map(|x| x.archive == entry.path()).unwrap_or(false);
+            let label = Text::new(entry_label(entry)).size(FONT_SIZE);
+            parent_column = match here {//the folder we are in stands out among its siblings
+                true => parent_column.push(Button::new(label).width(Length::Fill).on_press(Message::MillerParent(entry.path()))),
+                false => parent_column.push(Button::new(label).width(Length::Fill).on_press(Message::MillerParent(entry.path())).style(theme::Button::Text)),
+            };
+        }
+        let mut current_column = Column::new();
+        for i in self.visible_indexes() {
+            let label = Text::new(entry_label(&self.files[i])).size(FONT_SIZE);
+            current_column = match self.selection.contains(i) || self.selection.cursor == Some(i) {
+                _ if self.type_mode.is_some() && self.selection.cursor == Some(i) => current_column.push(TextInput::new("", self.type_mode.as_ref().unwrap()).on_input(Message::RenameUpdate).size(FONT_SIZE).id(self.rename_id.clone())),//renaming happens in place, same as on a tile
+                true => current_column.push(Button::new(label).width(Length::Fill).on_press(Message::MillerClicked(i))),
+                false => current_column.push(Button::new(label).width(Length::Fill).on_press(Message::MillerClicked(i)).style(theme::Button::Text)),
+            };
+        }
+        let preview_column: iced::Element<'_, Message, iced::Renderer<Theme>> = match &self.preview {
+            Some(Preview::Listing(entries)) => {
+                let mut column = Column::new().padding(SPACING / 2);
+                for entry in entries {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if self.show_hidden || !name.starts_with('.') {
+                        column = column.push(Text::new(entry_label(entry)).size(FONT_SIZE));
+                    }
+                }
+                column.into()
+            }
+            Some(Preview::Text(text)) | Some(Preview::Info(text)) => Text::new(text.clone()).size(FONT_SIZE).into(),
+            None => Space::new(0, 0).into(),
+        };
+        Row::new().height(Length::Fill)
+            .push(Scrollable::new(parent_column).width(Length::FillPortion(1)))
+            .push(Rule::vertical(RULE_WIDTH))
+            .push(Scrollable::new(current_column).id(self.miller_id.clone()).width(Length::FillPortion(2)))
+            .push(Rule::vertical(RULE_WIDTH))
+            .push(Container::new(Scrollable::new(preview_column)).padding(SPACING / 2).width(Length::FillPortion(2)))
+            .into()
+    }
+    fn open_location_edit(&mut self) -> iced::Command<Message> {//turn the path bar into a text field holding the current path
+        let mut value = self.shown_path().to_string_lossy().to_string();
+        if !value.ends_with('/') {//so the first tab lists what is in here
+            value.push('/');
+        }
+        let matches = completions(&value, &self.current_path);
+        self.location_edit = Some(LocationEdit { value, matches, error: None });
+        iced::Command::batch([text_input::focus(self.location_id.clone()), text_input::move_cursor_to_end(self.location_id.clone())])
+    }
+    fn complete_location(&mut self) -> iced::Command<Message> {//fill in as much as all the matches agree on
+        if let Some(edit) = &mut self.location_edit {
+            match common_prefix(&edit.matches) {
+                Some(x) if x.len() > edit.value.len() => {
+                    edit.matches = completions(&x, &self.current_path);
+                    edit.value = x;
+                }
+                _ => {}
+            }
+        }
+        text_input::move_cursor_to_end(self.location_id.clone())
+    }
+    fn submit_location(&mut self) {//go to the typed folder, or say what is wrong with it and leave the text as it is
+        let path = match &self.location_edit {
+            Some(x) => resolve(&x.value, &self.shown_path()),//relative input starts from where the path bar says we are, not from an archive's mirror
+            None => return,
+        };
+        if path == self.shown_path() {//nothing changed, inside an archive the shown path isn't a real one either
+            self.location_edit = None;
+            return;
+        }
+        if let Some(depth) = self.archive_stack.iter().rposition(|x| path.starts_with(&x.shown)) {//still inside an open archive, stay in archive mode and go to the matching folder of its mirror
+            let mount = self.archive_stack[depth].clone();
+            let mirrored = mount.mirror.join(path.strip_prefix(&mount.shown).unwrap_or(Path::new("")));
+            if mirrored.is_dir() {
+                self.location_edit = None;
+                self.remember_location();
+                self.archive_stack.truncate(depth + 1);
+                self.current_path = mirrored;
+                self.refresh_after_op();
+                return;
+            }
+        }
+        let error = if path.is_dir() {
+            self.location_edit = None;
+            self.change_directory(path);
+            return;
+        } else if path.exists() {
+            tr("Not a folder")
+        } else {
+            tr("No such folder")
+        };
+        if let Some(edit) = &mut self.location_edit {
+            edit.error = Some(error);
+        }
+    }
+    fn read_only(&self) -> bool {//neither the trash nor an archive can be changed in place
+        self.trash_view || !self.archive_stack.is_empty()
+    }
+    fn toggle_trash_view(&mut self) {//enter or leave the virtual trash location
+        self.remember_location();
+        self.show_trash(!self.trash_view);
+    }
+    fn show_trash(&mut self, on: bool) {
+        self.trash_view = on;
+        self.deletion_confirmation = false;
+        self.empty_confirmation = false;
+        self.refresh_after_op();
+    }
+    fn go_back_directory(&mut self) {//cd .., remembering where we were
+        self.remember_location();
+        self.pop_directory();
+    }
+    fn pop_directory(&mut self) {//pop an entry off of the current path, regenerate the file list and UI Files
+        if self.trash_view {//backing out of the trash returns to wherever we came from
+            self.show_trash(false);
+            return;
+        }
+        match self.archive_stack.last() {//the top of an archive backs out to the folder holding the archive
+            Some(mount) if self.current_path == mount.mirror => {
+                self.current_path = mount.archive.parent().unwrap_or(Path::new("/")).to_path_buf();
+                self.archive_stack.pop();
+            }
+            _ => {
+                self.current_path.pop();
+            }
+        }
         self.regen_files();
         sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
+        self.selection.clear();
         block_on(self.regen_ui_files());
     }
     fn change_sort(&mut self, reverse: bool) {//cycle through sort modes
@@ -277,76 +952,697 @@ impl Narwhal {
                 SortType::Files => SortType::Alphabetical,
             }; 
         }
-        sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
-        block_on(self.regen_ui_files());
+        sort_file_by_type(&mut self.files, self.sort_type.clone());
+        self.selection.clear();
+        block_on(self.regen_ui_files());
+    }
+    fn selected_paths(&self) -> Vec<PathBuf> {//paths of every selected entry
+        self.selection.indexes().into_iter().filter(|x| *x < self.files.len()).map(|x| self.files[x].path()).collect()
+    }
+    fn report_batch(&mut self, errors: Vec<FileOpError>) {//show the first failure of a batch, and how many others went wrong with it
+        self.op_error = match errors.len() {
+            0 => None,
+            1 => Some(errors[0].to_string()),
+            n => Some(format!("{} (+{} {})", errors[0], n - 1, tr("more errors"))),
+        };
+    }
+    fn rm_files(&mut self) {//send every selected file to the trash
+        let mut ops = vec![];
+        let mut errors = vec![];
+        for path in self.selected_paths() {
+            match trash_path(&path) {
+                Ok(entry) => ops.push(JournalOp::Trash { original: path.to_string_lossy().to_string(), trashed: entry.files_path.to_string_lossy().to_string(), info: entry.info_path.to_string_lossy().to_string() }),
+                Err(e) => errors.push(e),
+            }
+        }
+        if let Err(e) = self.journal.record(ops) {
+            errors.push(e);
+        }
+        self.report_batch(errors);
+        self.refresh_after_op();
+    }
+    fn rm_files_permanently(&mut self) {//remove every selected file for good, this function contains less sanity checks and should be used carefully
+        let mut errors = vec![];
+        for path in self.selected_paths() {
+            let result = match self.trash_entries.get(&path) {
+                Some(entry) if self.trash_view => delete_entry(entry),
+                _ => remove_path(&path),
+            };
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        self.report_batch(errors);
+        self.refresh_after_op();
+    }
+    fn delete_selected(&mut self) {//trash the selected files, or start deleting them for good if they already are in the trash
+        if self.trash_view {
+            self.confirm_permanent_delete();
+            return;
+        }
+        if self.selection.len() > 0 && !self.read_only() {
+            self.rm_files();
+        }
+        self.deletion_confirmation = false;
+    }
+    fn restore_selected(&mut self) {//move the selected trash entries back to their original locations
+        let mut errors = vec![];
+        for path in self.selected_paths() {
+            if let Some(entry) = self.trash_entries.get(&path) {
+                if let Err(e) = restore_entry(entry) {
+                    errors.push(e);
+                }
+            }
+        }
+        self.report_batch(errors);
+        self.refresh_after_op();
+    }
+    fn confirm_empty_trash(&mut self) {//the first press arms emptying the trash, the second one goes through with it
+        if self.empty_confirmation {
+            let result = empty_trash();
+            self.report(result);
+            self.refresh_after_op();
+        }
+        self.empty_confirmation = !self.empty_confirmation;
+    }
+    fn confirm_permanent_delete(&mut self) {//the first press arms permanent deletion, the second one goes through with it
+        if self.selection.len() > 0 && self.archive_stack.is_empty() {
+            if self.deletion_confirmation {
+                self.rm_files_permanently();
+            }
+            self.deletion_confirmation = !self.deletion_confirmation;
+        } else {
+            self.deletion_confirmation = false;
+        }
+    }
+    fn report<T>(&mut self, result: std::result::Result<T, FileOpError>) -> Option<T> {//stash a failed operation's error for the UI, passing successes through
+        match result {
+            Ok(x) => {
+                self.op_error = None;
+                Some(x)
+            }
+            Err(e) => {
+                self.op_error = Some(e.to_string());
+                None
+            }
+        }
+    }
+    fn refresh_after_op(&mut self) {//regenerate file list and UI files after the directory contents changed
+        self.miller_key = None;
+        self.size_cache.clear();//the operation may have changed any folder below this one, so count again
+        self.size_scan = None;
+        self.regen_files();
+        sort_file_by_type(&mut self.files, self.sort_type.clone());
+        self.selection.clear();
+        self.drag = None;//both hold indexes into the listing that was just replaced
+        self.drop_target = None;
+        block_on(self.regen_ui_files());
+        self.refresh_other_pane();
+    }
+    fn set_clipboard(&mut self, cut: bool) {//put the selection on the clipboard, replacing whatever was there
+        let paths: Vec<String> = self.selected_paths().into_iter().map(|x| x.to_string_lossy().to_string()).collect();
+        if paths.len() > 0 {
+            self.clipboard = Some(FileClipboard { paths, cut: cut && self.archive_stack.is_empty() });//members of an archive can only be copied out
+            block_on(self.regen_ui_files());
+        }
+    }
+    fn paste(&mut self) {//copy or move the clipboard into the current directory in the background
+        if self.read_only() {
+            return;
+        }
+        let clipboard = match self.clipboard.clone() {
+            Some(x) => x,
+            None => return,
+        };
+        let paths = clipboard.paths.iter().map(PathBuf::from).collect();
+        self.transfer_into(paths, self.current_path.clone(), clipboard.cut);
+    }
+    fn start_transfer(&mut self, title: String, mut items: Vec<TransferItem>, cut: bool, background: bool) {//ask about conflicts first if the policy says so, otherwise go right ahead
+        for item in items.iter_mut().filter(|x| x.policy == ConflictPolicy::Merge && !both_dirs(Path::new(&x.source), &x.dest)) {//a merge default can't settle a file conflict, same as answering merge in the prompt
+            item.policy = ConflictPolicy::Ask;
+        }
+        let conflicts: Vec<usize> = items.iter().enumerate().filter(|(_, x)| x.policy == ConflictPolicy::Ask && is_conflict(Path::new(&x.source), &x.dest)).map(|(i, _)| i).collect();
+        let pending = PendingTransfer { title, items, cut, background, conflicts, apply_all: false };
+        match pending.conflicts.is_empty() {
+            true => self.launch_transfer(pending),
+            false => self.pending_transfer = Some(pending),
+        }
+    }
+    fn resolve_conflict(&mut self, policy: ConflictPolicy) {//answer the current conflict, or every remaining one it applies to
+        let mut pending = match self.pending_transfer.take() {
+            Some(x) => x,
+            None => return,
+        };
+        let mut answered = vec![];
+        for (n, i) in pending.conflicts.iter().enumerate() {
+            if n > 0 && !pending.apply_all {
+                break;
+            }
+            let item = &mut pending.items[*i];
+            if policy == ConflictPolicy::Merge && !both_dirs(Path::new(&item.source), &item.dest) {//files can't be merged, keep asking about those
+                continue;
+            }
+            item.policy = policy;
+            answered.push(*i);
+        }
+        pending.conflicts.retain(|x| !answered.contains(x));
+        match pending.conflicts.is_empty() {
+            true => self.launch_transfer(pending),
+            false => self.pending_transfer = Some(pending),
+        }
+    }
+    fn launch_transfer(&mut self, pending: PendingTransfer) {//paste runs as a job, renames are quick enough to run right here
+        if let Some(clipboard) = &self.clipboard {//a cut clipboard is emptied once its items are on their way
+            if clipboard.cut && pending.items.iter().any(|x| clipboard.contains(&x.source)) {
+                self.clipboard = None;
+                block_on(self.regen_ui_files());
+            }
+        }
+        if pending.background {
+            self.jobs.push(Job::spawn_transfer(self.next_job_id, pending.title, pending.items, pending.cut));
+            self.next_job_id += 1;
+        } else {
+            let mut outcome = run_transfer(pending.items, pending.cut, None);
+            if let Err(e) = self.journal.record(outcome.ops) {
+                outcome.errors.push(e);
+            }
+            self.report_batch(outcome.errors);
+            self.refresh_after_op();
+        }
+    }
+    fn poll_jobs(&mut self) {//collect finished jobs, journal what they did and refresh the grid
+        let mut finished = false;
+        let mut errors = vec![];
+        let mut opened = None;
+        let mut i = 0;
+        while i < self.jobs.len() {
+            match self.jobs[i].take_outcome() {
+                Some(outcome) => {
+                    match self.opening_archive.take() {//an archive is only stepped into if we are still where it was opened from
+                        Some((id, from, mount)) if id == self.jobs[i].id => {
+                            if outcome.errors.is_empty() && self.location() == from {
+                                opened = Some(mount);
+                            }
+                        }
+                        x => self.opening_archive = x,
+                    }
+                    if let Err(e) = self.journal.record(outcome.ops) {
+                        errors.push(e);
+                    }
+                    errors.extend(outcome.errors.into_iter().filter(|x| !matches!(x, FileOpError::Cancelled)));
+                    self.jobs.remove(i);
+                    finished = true;
+                }
+                None => i += 1,
+            }
+        }
+        if finished {
+            if let Some(mount) = opened {
+                self.enter_archive(mount);
+            }
+            if let Some(properties) = &self.properties {//reload the panel so it shows what actually stuck
+                if let Ok(x) = Properties::load(&properties.path, properties.mime.clone()) {
+                    self.properties = Some(x);
+                }
+            }
+            self.report_batch(errors);
+            self.refresh_after_op();
+        }
+    }
+    fn touch(&mut self) -> iced::Command<Message> {
+        self.create_entry(false)
+    }
+    fn mkdir(&mut self) -> iced::Command<Message> {
+        self.create_entry(true)
+    }
+    fn create_entry(&mut self, is_dir: bool) -> iced::Command<Message> {//make a file or folder under a free name, then select it and go straight into renaming it
+        if self.read_only() {
+            return iced::Command::none();
+        }
+        let base = match is_dir {
+            true => tr("New Folder"),
+            false => tr("New File"),
+        };
+        let path = self.current_path.join(numbered_name(&self.current_path, &base));
+        let result = match is_dir {
+            true => create_dir(&path),
+            false => create_file(&path),
+        };
+        let created = self.report(result).is_some();
+        if created {
+            let saved = self.journal.record(vec![JournalOp::Create { path: path.to_string_lossy().to_string(), is_dir }]);
+            self.report(saved);
+        }
+        self.refresh_after_op();
+        match created {
+            true => self.select_and_rename(&path),
+            false => iced::Command::none(),
+        }
+    }
+    fn select_and_rename(&mut self, path: &Path) -> iced::Command<Message> {//put the cursor on a freshly made entry and open the rename editor on it
+        match self.files.iter().position(|x| x.path() == path) {
+            Some(index) => {
+                self.selection.select_only(index);
+                let scroll = self.scroll_to_cursor();//a new entry can land anywhere in the listing
+                block_on(self.regen_ui_files());
+                iced::Command::batch([scroll, self.start_rename()])
+            }
+            None => iced::Command::none(),
+        }
+    }
+    fn toggle_templates(&mut self) {//open or close the template submenu, collecting templates and their icons on the way in
+        if self.templates.is_some() || self.read_only() {
+            self.templates = None;
+            return;
+        }
+        let dir = match get_templates_dir() {
+            Some(x) => x,
+            None => {
+                self.op_error = Some(tr("The templates directory is turned off in user-dirs.dirs"));
+                return;
+            }
+        };
+        let mut paths: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Ok(read_output) => read_output.flatten().filter(|x| !x.file_name().to_string_lossy().starts_with('.')).map(|x| x.path()).collect(),
+            Err(..) => vec![],
+        };
+        if paths.is_empty() {
+            self.op_error = Some(format!("{} {}", tr("No templates in"), dir));
+            return;
+        }
+        paths.sort();
+        let mut templates = vec![];
+        for path in paths {
+            let (cache_changes, icon) = block_on(get_file_icon(self.icon_cache.clone(), path.to_string_lossy().to_string(), self.icn_theme.clone(), self.icn_size));
+            if let Some(changes) = cache_changes {
+                self.icon_cache.extend(changes.into_iter());
+            }
+            templates.push((path, icon));
+        }
+        self.templates = Some(templates);
+    }
+    fn create_from_template(&mut self, index: usize) -> iced::Command<Message> {//copy a template into the current directory under a free name, then rename it
+        let source = match self.templates.take().and_then(|x| x.get(index).map(|y| y.0.clone())) {
+            Some(x) => x,
+            None => return iced::Command::none(),
+        };
+        let name = source.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        let dest = self.current_path.join(unique_name(&self.current_path, &name));
+        let result = copy_into_place(&source, &dest);
+        let created = self.report(result).is_some();
+        if created {
+            let saved = self.journal.record(vec![JournalOp::Copy { source: source.to_string_lossy().to_string(), created: dest.to_string_lossy().to_string() }]);
+            self.report(saved);
+        }
+        self.refresh_after_op();
+        match created {
+            true => self.select_and_rename(&dest),
+            false => iced::Command::none(),
+        }
+    }
+    fn start_rename(&mut self) -> iced::Command<Message> {//open the editor on the cursor's tile, filled in with its name and the cursor just before the extension
+        let cursor = match self.selection.cursor {
+            Some(x) if !self.read_only() => x,
+            _ => return iced::Command::none(),
+        };
+        let name = self.files[cursor].file_name().to_string_lossy().to_string();
+        let stem_length = split_name(&name).0.chars().count();
+        self.type_mode = Some(name);
+        iced::Command::batch(vec![
+            text_input::focus(self.rename_id.clone()),
+            text_input::move_cursor_to(self.rename_id.clone(), stem_length),
+        ])
+    }
+    fn rename_check(&self) -> std::result::Result<(), FileOpError> {//live validation of the name being typed
+        let (value, cursor) = match (&self.type_mode, self.selection.cursor) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Ok(()),
+        };
+        validate_name(value)?;
+        let src_path = self.files[cursor].path();
+        let dest = src_path.with_file_name(value);
+        match dest != src_path && fs::symlink_metadata(&dest).is_ok() {
+            true => Err(FileOpError::AlreadyExists(dest)),
+            false => Ok(()),
+        }
+    }
+    fn finish_rename(&mut self, apply: bool) {//close the editor, renaming first if asked to, an invalid name keeps it open
+        if apply {
+            match self.rename_check() {
+                Err(FileOpError::InvalidName(..)) => return,
+                _ => self.rename(),
+            }
+        }
+        self.type_mode = None;
+    }
+    fn rename(&mut self) {//existing names are left to the conflict policy
+        let (new_name, cursor) = match (self.type_mode.clone(), self.selection.cursor) {
+            (Some(x), Some(y)) if !self.read_only() => (x, y),
+            _ => return,
+        };
+        let src_path = self.files[cursor].path();
+        if src_path.file_name().map(|x| x.to_string_lossy() == new_name).unwrap_or(false) {
+            return;
+        }
+        let dest = src_path.with_file_name(&new_name);
+        let item = TransferItem { source: src_path.to_string_lossy().to_string(), dest, policy: self.conflict_policy };
+        self.start_transfer(format!("{} {}", tr("Renaming to"), new_name), vec![item], true, false);
+    }
+    fn open_batch_rename(&mut self) {//start a batch rename for the selection
+        if self.read_only() {
+            return;
+        }
+        let paths = self.selected_paths();
+        if paths.len() > 0 {
+            self.batch_rename = Some(BatchRename::new(paths));
+        }
+    }
+    fn apply_batch_rename(&mut self) {//rename everything in the dialog, refusing outright if the preview flagged anything
+        let batch = match &self.batch_rename {
+            Some(x) => x,
+            None => return,
+        };
+        let previews = match batch.preview() {
+            Ok(x) => x,
+            Err(e) => {
+                self.op_error = Some(e);
+                return;
+            }
+        };
+        if let Some(bad) = previews.iter().find(|x| x.problem.is_some()) {
+            self.op_error = Some(format!("{}: {}", bad.new_name, bad.problem.clone().unwrap_or_default()));
+            return;
+        }
+        let renames = previews.into_iter().map(|x| (x.source.clone(), x.source.with_file_name(&x.new_name))).collect();
+        let (ops, mut errors) = apply_renames(renames);
+        if let Err(e) = self.journal.record(ops) {
+            errors.push(e);
+        }
+        self.report_batch(errors);
+        self.batch_rename = None;
+        self.refresh_after_op();
+    }
+    fn edit_names(&mut self) -> iced::Command<Message> {//hand the selected names to $EDITOR, the edited list is applied once it exits
+        if self.read_only() || self.editor_rename.is_some() {
+            return iced::Command::none();
+        }
+        let paths = self.selected_paths();
+        if paths.len() == 0 {
+            return iced::Command::none();
+        }
+        let (program, args) = match editor_command() {
+            Ok(x) => x,
+            Err(e) => {
+                self.op_error = Some(e);
+                return iced::Command::none();
+            }
+        };
+        let list = match self.report(write_edit_list(&paths)) {
+            Some(x) => x,
+            None => return iced::Command::none(),
+        };
+        self.editor_rename = Some(paths);
+        iced::Command::perform(async move {//the editor gets a thread of its own, waiting on it here would tie up an executor worker
+            let (sender, receiver) = oneshot::channel();
+            std::thread::spawn(move || sender.send(run_editor(program, args, list)));
+            receiver.await.unwrap_or_else(|_| Err(String::from("the editor stopped without an answer")))
+        }, Message::EditorClosed)
+    }
+    fn apply_edited_names(&mut self, result: std::result::Result<(), String>) {//trash the removed lines, then rename the rest
+        let paths = match self.editor_rename.take() {
+            Some(x) => x,
+            None => return,
+        };
+        let (renames, removed) = match result.and_then(|_| read_edit_list(&paths)) {
+            Ok(x) => x,
+            Err(e) => {
+                self.op_error = Some(e);
+                return;
+            }
+        };
+        let mut ops = vec![];
+        let mut errors = vec![];
+        for path in removed {
+            match trash_path(&path) {
+                Ok(entry) => ops.push(JournalOp::Trash { original: path.to_string_lossy().to_string(), trashed: entry.files_path.to_string_lossy().to_string(), info: entry.info_path.to_string_lossy().to_string() }),
+                Err(e) => errors.push(e),
+            }
+        }
+        let (rename_ops, rename_errors) = apply_renames(renames);
+        ops.extend(rename_ops);
+        errors.extend(rename_errors);
+        if let Err(e) = self.journal.record(ops) {
+            errors.push(e);
+        }
+        self.report_batch(errors);
+        self.refresh_after_op();
+    }
+    fn open_properties(&mut self) {//show the properties panel for the cursor
+        let cursor = match self.selection.cursor {
+            Some(x) => x,
+            None => return,
+        };
+        let path = self.files[cursor].path();
+        let mime = get_file_mimetype(path.to_string_lossy().to_string());
+        self.properties = self.report(Properties::load(&path, mime));
+        self.update_size_scan();
+    }
+    fn update_size_scan(&mut self) {//count the selected folders, or the one in the properties panel, starting over only when that set changes
+        self.poll_size_scan();
+        let paths: Vec<PathBuf> = match &self.properties {
+            Some(x) if x.is_dir => vec![x.path.clone()],
+            Some(..) => vec![],
+            None => self.selected_paths().into_iter().filter(|x| fs::symlink_metadata(x).map(|m| m.is_dir()).unwrap_or(false)).collect(),
+        };
+        if paths.is_empty() {
+            self.size_scan = None;
+            return;
+        }
+        if self.size_scan.as_ref().map(|x| x.paths == paths).unwrap_or(false) {
+            return;
+        }
+        self.size_scan = Some(SizeScan::start(paths, &self.size_cache));
+    }
+    fn poll_size_scan(&mut self) {//move finished directories into the cache so coming back to them is instant
+        if let Some(scan) = &self.size_scan {
+            for (path, entry) in scan.take_finished() {
+                self.size_cache.insert(path, entry);
+            }
+        }
+    }
+    fn size_summary(&self) -> Option<String> {//running total of the current size count
+        let scan = self.size_scan.as_ref()?;
+        let size = scan.total();
+        let counting = match scan.is_done() {
+            true => String::new(),
+            false => format!(" ({})", tr("counting…")),
+        };
+        Some(format!("{} · {} {}, {} {}{}", format_size(size.bytes), size.files, tr("files"), size.dirs, tr("folders"), counting))
+    }
+    fn apply_properties(&mut self) {//write the panel back to disk as a job, poll_jobs reloads the panel once it is done
+        if self.read_only() {//inside an archive this would only change the throwaway mirror
+            return;
+        }
+        let properties = match &self.properties {
+            Some(x) => x.clone(),
+            None => return,
+        };
+        let name = properties.path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        let title = format!("{} {}", tr("Changing properties of"), name);
+        self.jobs.push(Job::spawn(self.next_job_id, title, move |progress| JobOutcome { ops: vec![], errors: properties.apply(Some(progress)) }));//a recursive change can walk a big tree
+        self.next_job_id += 1;
+    }
+    fn selected_archives(&self, paths: &[PathBuf]) -> Vec<(PathBuf, ArchiveFormat)> {//the paths xdg-mime recognises as an archive we can read
+        paths.iter().filter_map(|x| {
+            let name = x.file_name().map(|y| y.to_string_lossy().to_string()).unwrap_or_default();
+            format_for_mime(&get_file_mimetype(x.to_string_lossy().to_string()), &name).map(|y| (x.clone(), y))
+        }).collect()
+    }
+    fn open_compress(&mut self) -> iced::Command<Message> {//ask for the archive's name, suggesting one after the selection
+        let paths = self.selected_paths();
+        if self.read_only() || paths.len() == 0 {
+            return iced::Command::none();
+        }
+        let base = match paths.len() {
+            1 => paths[0].file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default(),
+            _ => tr("Archive"),
+        };
+        let target = unique_name(&self.current_path, &format!("{}.zip", base));
+        self.archive_prompt = Some(ArchivePrompt { paths, compress: true, target });
+        text_input::focus(self.prompt_id.clone())
+    }
+    fn open_extract_to(&mut self) -> iced::Command<Message> {//ask where to extract the selected archives, starting from here
+        let paths: Vec<PathBuf> = self.selected_archives(&self.selected_paths()).into_iter().map(|x| x.0).collect();
+        if self.read_only() || paths.len() == 0 {
+            self.op_error = Some(tr("Nothing selected is an archive"));
+            return iced::Command::none();
+        }
+        let target = self.current_path.to_string_lossy().to_string();
+        self.archive_prompt = Some(ArchivePrompt { paths, compress: false, target });
+        text_input::focus(self.prompt_id.clone())
+    }
+    fn confirm_archive_prompt(&mut self) {//kick off whatever the prompt was asking about, relative targets start from the current directory
+        let prompt = match self.archive_prompt.take() {
+            Some(x) => x,
+            None => return,
+        };
+        let target = self.current_path.join(prompt.target.trim());
+        match prompt.compress {
+            true => {
+                let title = format!("{} {}", tr("Compressing into"), target.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default());
+                self.jobs.push(Job::spawn(self.next_job_id, title, move |progress| run_compress(prompt.paths, target, Some(progress))));
+                self.next_job_id += 1;
+            }
+            false => {
+                let archives = self.selected_archives(&prompt.paths);
+                self.extract_archives(archives, target);
+            }
+        }
+    }
+    fn extract_here(&mut self) {//every selected archive gets its own folder next to it, unless it holds a single entry
+        if self.read_only() {
+            return;
+        }
+        let archives = self.selected_archives(&self.selected_paths());
+        if archives.is_empty() {
+            self.op_error = Some(tr("Nothing selected is an archive"));
+            return;
+        }
+        self.extract_archives(archives, self.current_path.clone());
+    }
+    fn extract_archives(&mut self, archives: Vec<(PathBuf, ArchiveFormat)>, dest_dir: PathBuf) {
+        let title = match archives.len() {
+            1 => format!("{} {}", tr("Extracting"), archives[0].0.file_name().map(|x| archive_stem(&x.to_string_lossy())).unwrap_or_default()),
+            n => format!("{} {} {}", tr("Extracting"), n, tr("archives")),
+        };
+        self.jobs.push(Job::spawn(self.next_job_id, title, move |progress| run_extract(archives, dest_dir, Some(progress))));
+        self.next_job_id += 1;
+    }
+    fn update_drop_target(&mut self) {//work out which folder tile or bookmark is under the cursor while something is dragged over the window
+        let position = match self.cursor_position {//not every platform reports the cursor during a drag from another application
+            Some(x) => x,
+            None => {
+                self.drop_target = None;
+                return;
+            }
+        };
+        let tile = match (&self.batch_rename, &self.properties) {//tiles are only there while the grid is
+            (None, None) if self.view_mode == ViewMode::Grid => {
+                let order = self.visible_indexes();
+                tile_at(position, pane_offset(self.desired_cols, self.focus_right), self.grid_offset, self.desired_cols, order.len()).map(|x| order[x])
+            }
+            _ => None,
+        };
+        let templates = self.templates.as_ref().map(|x| x.len()).unwrap_or(0);
+        self.drop_target = match (tile, bookmark_at(position, templates, self.bookmarked_dirs.len())) {
+            (Some(x), _) if self.files.get(x).map(|y| y.path().is_dir()).unwrap_or(false) && self.drag.as_ref().map(|y| y.index != x).unwrap_or(true) => Some(DropTarget::Tile(x)),//a tile can't be dropped onto itself
+            (_, Some(x)) => Some(DropTarget::Bookmark(x)),
+            _ if self.split.is_some() && self.view_mode == ViewMode::Grid && in_pane(position, pane_offset(self.desired_cols, !self.focus_right), self.desired_cols) => Some(DropTarget::OtherPane),
+            _ => None,
+        };
+        for ui_file in &mut self.ui_files {
+            ui_file.targeted = self.drop_target == Some(DropTarget::Tile(ui_file.original_index));
+        }
+    }
+    fn drop_dir(&self) -> Option<PathBuf> {//where a drop would land, the current directory unless a folder or bookmark is under the cursor
+        match self.drop_target {
+            Some(DropTarget::Bookmark(x)) => Some(PathBuf::from(&self.bookmarked_dirs[x].path)),
+            Some(DropTarget::OtherPane) => self.split.as_ref().filter(|x| !x.read_only()).map(|x| x.current_path.clone()),
+            _ if self.read_only() => None,//folders in the trash or inside an archive can't take anything either
+            Some(DropTarget::Tile(x)) => self.files.get(x).map(|y| y.path()),
+            None => Some(self.current_path.clone()),
+        }
+    }
+    fn drop_files(&mut self, paths: Vec<PathBuf>, cut: bool) {//copy or move files dragged in from elsewhere into the drop target
+        let dir = match self.drop_dir() {
+            Some(x) => x,
+            None => {
+                self.op_error = Some(tr("Files can't be dropped here"));
+                return;
+            }
+        };
+        self.transfer_into(paths, dir, cut);
+    }
+    fn transfer_into(&mut self, paths: Vec<PathBuf>, dir: PathBuf, cut: bool) {//copy or move paths into dir in the background, each keeping its name
+        let title = match cut {
+            true => format!("{} {} {}", tr("Moving"), paths.len(), tr("items")),
+            false => format!("{} {} {}", tr("Copying"), paths.len(), tr("items")),
+        };
+        let mut items = vec![];
+        for source in paths {
+            let dest = match source.file_name() {
+                Some(name) => dir.join(name),
+                None => continue,
+            };
+            items.push(TransferItem { source: source.to_string_lossy().to_string(), dest, policy: self.conflict_policy });
+        }
+        self.start_transfer(title, items, cut, true);
     }
-    fn rm_file(&mut self, index: usize) {//remove a file, this function contains less sanity checks and should be used carefully
-        let path = self.files[index].path().to_string_lossy().to_string();
-        let is_directory = match self.files[index].metadata() {
-            Ok(x) => x.is_dir(),
-            Err(..) => false,
+    fn start_drag(&mut self, index: usize) {//a press on a tile, dragging the whole selection if the tile is part of it
+        let paths = match (self.selection.contains(index), self.files.get(index)) {
+            (true, _) => self.selected_paths(),
+            (false, Some(x)) => vec![x.path()],
+            (false, None) => return,
         };
-        if is_directory {
-            match fs::remove_dir_all(path) {
-                Ok(..) => println!("successfully removed"),
-                Err(e) => println!("{e}")
-            }
-        } else {
-            match fs::remove_file(path) {
-                Ok(..) => println!("successfully removed"),
-                Err(e) => println!("{e}")
+        self.drag = Some(DragState { index, paths, origin: self.cursor_position, moved: false });
+    }
+    fn follow_drag(&mut self, position: Point) {//the first reported position is where the drag started
+        if let Some(drag) = &mut self.drag {
+            match drag.origin {
+                Some(origin) => drag.moved = drag.moved || moved_far(origin, position),
+                None => drag.origin = Some(position),
             }
         }
-        self.regen_files();
-        sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
-        block_on(self.regen_ui_files());
     }
-    fn mv_file(&mut self) {//move a file to another location, no sanity checks
-        let target = self.mv_target.clone().unwrap();
-        let path = self.current_path.to_string_lossy().to_string();
-        Command::new("mv").arg(target).arg(path).output().unwrap();
-        self.mv_target = None;
-        self.regen_files();
-        sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
-        block_on(self.regen_ui_files());
-    }
-    fn cp_file(&mut self) {//copy a file to another location, no sanity checks
-        let target = self.cp_target.clone().unwrap();
-        let path = self.current_path.to_string_lossy().to_string();
-        Command::new("cp").arg(target).arg(path).output().unwrap();
-        self.cp_target = None;
-        self.regen_files();
-        sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
+    fn finish_drag(&mut self) {//a release that never moved far is a click, otherwise move or with ctrl copy onto the folder or bookmark under the cursor
+        let drag = match self.drag.take() {
+            Some(x) => x,
+            None => return,
+        };
+        if !drag.moved {
+            if drag.index < self.files.len() {
+                self.click_file(drag.index);
+            }
+            return;
+        }
+        self.update_drop_target();
+        let target = self.drop_target.take();
+        self.cursor_position = None;
         block_on(self.regen_ui_files());
+        let dir = match (target, self.drop_dir()) {//letting go over nothing in particular does nothing
+            (Some(..), Some(x)) => x,
+            _ => return,
+        };
+        if drag.paths.contains(&dir) {
+            return;
+        }
+        let cut = !self.modifiers.control() && self.archive_stack.is_empty();//members of an archive can only be copied out
+        self.transfer_into(drag.paths, dir, cut);
     }
-    fn touch(&mut self) {
-        let path = format!("{}/NewFile", self.current_path.to_string_lossy().to_string());
-        Command::new("touch").arg(path).output().unwrap();
-        self.regen_files();
-        sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
-        block_on(self.regen_ui_files());
+    fn transfer_across(&mut self, cut: bool) {//copy or move the selection straight into the other pane's folder
+        let dir = match &self.split {
+            Some(pane) if !pane.read_only() => pane.current_path.clone(),
+            Some(..) => {
+                self.op_error = Some(tr("Files can't be dropped here"));
+                return;
+            }
+            None => return,
+        };
+        let paths = self.selected_paths();
+        if paths.is_empty() || paths.contains(&dir) {
+            return;
+        }
+        self.transfer_into(paths, dir, cut && !self.read_only());//the trash and archives can only be copied out of
     }
-    fn mkdir(&mut self) {
-        let path = format!("{}/NewFolder", self.current_path.to_string_lossy().to_string());
-        Command::new("mkdir").arg(path).output().unwrap();
-        self.regen_files();
-        sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
-        block_on(self.regen_ui_files());
+    fn undo(&mut self) {//revert the latest file operation
+        let result = self.journal.undo();
+        self.report(result);
+        self.refresh_after_op();
     }
-    fn rename(&mut self) {
-        let src_path = self.files[self.last_clicked_file.unwrap()].path().to_string_lossy().to_string();
-        let dest_path = format!("{}/{}", self.current_path.to_string_lossy().to_string(), self.type_mode.clone().unwrap());
-        Command::new("mv").arg(src_path).arg(dest_path).output().unwrap();
-        self.regen_files();
-        sort_file_by_type(&mut self.files, self.sort_type.clone());
-        self.last_clicked_file = None;
-        block_on(self.regen_ui_files());
+    fn redo(&mut self) {//run the latest undone file operation again
+        let result = self.journal.redo();
+        self.report(result);
+        self.refresh_after_op();
     }
 }
 fn sort_file_by_type(input: &mut Vec<DirEntry>, sort_type: SortType) {//sort files based on the chosen SortType
@@ -372,14 +1668,7 @@ impl Application for Narwhal {
     type Theme = Theme;
     type Flags = ();
     fn new(_flags: ()) -> (Self, iced::Command<Self::Message>) {//initialize program
-        let mut narwhal = Self::default();
-        use cosmic_time::button;
-        let un_mitosis = chain![RENAME_BTN,
-            button(Duration::ZERO).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-            button(Duration::from_millis(500)).width(Length::Fixed(75.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-            button(Duration::from_millis(500)).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-        ];
-        narwhal.anims.set_chain(un_mitosis).start();
+        let narwhal = Self::default();
         (
             narwhal,
             iced::Command::none()
@@ -394,15 +1683,106 @@ impl Application for Narwhal {
             let temp = file.file_name().to_string_lossy().to_string();
             temp_files.push(temp);
         };
-        match message {
-            Message::FileClicked(x) => {//a file was clicked, interact it
-                self.interact_selected_entry(x);
+        let before = self.location();
+        let command = match message {
+            Message::DragStart(x) => {//a file was pressed, whether this selects or interacts with it or drags it is settled on release
+                self.start_drag(x);
+                iced::Command::none()
+            },
+            Message::DragReleased => {
+                self.finish_drag();
                 iced::Command::none()
             },
-            Message::GoBack(x) => {//go back a directory
+            Message::GoBack(x) => {//go back a directory, jumping several path bar segments is a single step in the history
+                self.remember_location();
                 for _i in 0..x {
-                    self.go_back_directory();
+                    self.pop_directory();
+                }
+                iced::Command::none()
+            },
+            Message::HistoryBack => {
+                self.history_back();
+                iced::Command::none()
+            },
+            Message::HistoryForward => {
+                self.history_forward();
+                iced::Command::none()
+            },
+            Message::HistoryChosen(location) => {//picking a recent location counts as going somewhere new
+                self.remember_location();
+                self.go_to(location);
+                iced::Command::none()
+            },
+            Message::NewTab => {
+                self.open_tab(self.location(), true);
+                iced::Command::none()
+            },
+            Message::TabSelected(x) => {
+                self.switch_tab(x);
+                iced::Command::none()
+            },
+            Message::TabClosed(x) => {
+                self.close_tab(x);
+                iced::Command::none()
+            },
+            Message::OpenInNewTab(x) => {
+                self.open_in_new_tab(x);
+                iced::Command::none()
+            },
+            Message::SplitToggled => {
+                self.toggle_split();
+                iced::Command::none()
+            },
+            Message::PaneFocused => {
+                self.switch_pane();
+                iced::Command::none()
+            },
+            Message::CopyAcross => {
+                self.transfer_across(false);
+                iced::Command::none()
+            },
+            Message::MoveAcross => {
+                self.transfer_across(true);
+                iced::Command::none()
+            },
+            Message::ViewToggled => {
+                self.toggle_view_mode();
+                iced::Command::none()
+            },
+            Message::MillerClicked(x) => {//the Miller view has no drag and drop, a press is a click right away
+                self.click_file(x);
+                iced::Command::none()
+            },
+            Message::MillerParent(path) => {//a sibling folder in the parent column
+                if path.is_dir() {
+                    self.change_directory(path);
+                }
+                iced::Command::none()
+            },
+            Message::GridScrolled(viewport) => {//only rebuild the tiles once the margin runs out
+                self.grid_offset = viewport.absolute_offset().y;
+                self.grid_height = viewport.bounds().height;
+                let first_row = (self.grid_offset / EST_HEIGHT as f32) as usize;
+                if first_row < self.ui_start_row || first_row + self.desired_rows as usize + 1 > self.ui_end_row {
+                    block_on(self.regen_ui_files());
+                }
+                iced::Command::none()
+            },
+            Message::LocationEditOpen => self.open_location_edit(),
+            Message::LocationEdited(x) => {//completions follow along with the typing
+                let matches = completions(&x, &self.current_path);
+                self.location_edit = Some(LocationEdit { value: x, matches, error: None });
+                iced::Command::none()
+            },
+            Message::LocationSubmitted => {
+                self.submit_location();
+                iced::Command::none()
+            },
+            Message::LocationChosen(x) => {//picking a match from the dropdown goes straight there
+                if let Some(edit) = &mut self.location_edit {
+                    edit.value = x;
                 }
+                self.submit_location();
                 iced::Command::none()
             },
             Message::SortChanged => {//change sort mode
@@ -410,8 +1790,7 @@ impl Application for Narwhal {
                 iced::Command::none()
             }
             Message::HiddenChanged => {//change hidden flag
-                self.show_hidden = !self.show_hidden;
-                block_on(self.regen_ui_files());
+                self.toggle_hidden();
                 iced::Command::none()
             }
             Message::BookmarkCurrent => {//bookmark or un-bookmark current dir
@@ -436,11 +1815,19 @@ impl Application for Narwhal {
                 iced::Command::none()
             }
             Message::BookmarkClicked(index) => {//go to the bookmark's chosen dir
-                self.current_path = PathBuf::from(self.bookmarked_dirs[index].path.clone());
-                self.regen_files();
-                sort_file_by_type(&mut self.files, self.sort_type.clone());
-                self.last_clicked_file = None;
-                block_on(self.regen_ui_files());
+                self.change_directory(PathBuf::from(self.bookmarked_dirs[index].path.clone()));
+                iced::Command::none()
+            }
+            Message::TrashClicked => {//enter or leave the trash
+                self.toggle_trash_view();
+                iced::Command::none()
+            }
+            Message::RestoreClicked => {//put the selected trash entry back
+                self.restore_selected();
+                iced::Command::none()
+            }
+            Message::EmptyTrashClicked => {//do sanity checks then empty the trash
+                self.confirm_empty_trash();
                 iced::Command::none()
             }
             Message::KeyboardUpdate(kb_event) => {//send to keyboard parser
@@ -454,19 +1841,19 @@ impl Application for Narwhal {
                         let old_rows = self.desired_rows;
                         if width > SIDEBAR_WIDTH as u32 {
                             let adjusted_width = width - SIDEBAR_WIDTH as u32;
-                            self.desired_cols = adjusted_width / EST_LENGTH;
-                        }
-                        if self.desired_cols == 0 {
-                            self.desired_cols = 1;
+                            self.window_cols = adjusted_width / EST_LENGTH;
                         }
+                        self.fit_panes();
                         if height > EST_HEIGHT {
                             let adjusted_height = height;
                             self.desired_rows = adjusted_height / EST_HEIGHT;
                         }
+                        self.grid_height = height.saturating_sub(3 * TOP_HEIGHT as u32 + 2 * RULE_WIDTH as u32) as f32;//tab strip, function buttons and path bar, until the scrollable reports its real size
                         if old_cols == self.desired_cols && old_rows == self.desired_rows {
 
                         } else {
                             block_on(self.regen_ui_files());
+                            self.refresh_other_pane();
                         }
                         iced::Command::none()
                     },
@@ -476,119 +1863,233 @@ impl Application for Narwhal {
                         let cached_contents = toml::to_string(&yes).unwrap();
                         let cache_home = format!("{}/NarwhalFM", get_cache_home());
                         fs::write(cache_home, cached_contents).unwrap();
-                        let config_file = Config { sort_mode: encode_sort(self.sort_type.clone()), show_hidden: self.show_hidden, bookmarks: self.bookmarked_dirs.clone(), icn_theme: self.icn_theme.clone(), icn_size: self.icn_size };
+                        let config_file = Config { sort_mode: encode_sort(self.sort_type.clone()), show_hidden: self.show_hidden, bookmarks: self.bookmarked_dirs.clone(), icn_theme: self.icn_theme.clone(), icn_size: self.icn_size, conflict_policy: encode_conflict(self.conflict_policy), tabs: self.saved_tabs(), active_tab: self.active_tab, view_mode: encode_view(self.view_mode) };
                         let config_text = toml::to_string(&config_file).unwrap();
                         let config_home = format!("{}/Oceania/NarwhalFM.toml", get_config_home());
                         fs::write(config_home, config_text).unwrap();
+                        clear_mirrors();
                         iced::window::close()
                     },
                     iced::window::Event::Focused => {iced::Command::none()},
                     iced::window::Event::Unfocused => {iced::Command::none()},
-                    iced::window::Event::FileHovered(_) => {iced::Command::none()},
-                    iced::window::Event::FileDropped(_) => {iced::Command::none()},
-                    iced::window::Event::FilesHoveredLeft => {iced::Command::none()},
+                    iced::window::Event::FileHovered(path) => {//one event per file, keep them all for the drop indicator
+                        if !self.drop_hover.contains(&path) {
+                            self.drop_hover.push(path);
+                        }
+                        self.update_drop_target();
+                        iced::Command::none()
+                    },
+                    iced::window::Event::FileDropped(path) => {//one event per file as well, gather them and start a single transfer once no more have come in for a moment
+                        self.dropped.push(path);
+                        self.dropped_at = Some(std::time::Instant::now());
+                        iced::Command::none()
+                    },
+                    iced::window::Event::FilesHoveredLeft => {
+                        self.drop_hover = vec![];
+                        self.drop_target = None;
+                        self.cursor_position = None;
+                        block_on(self.regen_ui_files());
+                        iced::Command::none()
+                    },
                 }
             }
-            Message::DeleteClicked => {//do sanity checks then rm file
-                match self.last_clicked_file {
-                    Some(x) => {
-                        if self.deletion_confirmation {
-                            self.rm_file(x);
-                        }
-                        self.deletion_confirmation = !self.deletion_confirmation;
-                    }
-                    None => {
-                        self.deletion_confirmation = false;
-                    }
+            Message::DeleteClicked => {//do sanity checks then trash file
+                self.delete_selected();
+                iced::Command::none()
+            }
+            Message::PermDeleteClicked => {//do sanity checks then rm file for good
+                self.confirm_permanent_delete();
+                iced::Command::none()
+            }
+            Message::CutClicked => {//put the selection on the clipboard to be moved
+                self.set_clipboard(true);
+                iced::Command::none()
+            }
+            Message::CopyClicked => {//put the selection on the clipboard to be copied
+                self.set_clipboard(false);
+                iced::Command::none()
+            }
+            Message::PasteClicked => {//do sanity checks then paste the clipboard here
+                self.paste();
+                iced::Command::none()
+            }
+            Message::JobTick => {//check in on background jobs and size counts
+                self.poll_jobs();
+                self.poll_size_scan();
+                iced::Command::none()
+            }
+            Message::PauseJob(id) => {
+                if let Some(job) = self.jobs.iter().find(|x| x.id == id) {
+                    job.toggle_pause();
                 }
                 iced::Command::none()
             }
-            Message::MvClicked => {//do sanity checks then mv file
-                match self.mv_target {
-                    Some(..) => {
-                        self.mv_file();
-                    }
-                    None => {
-                        match self.last_clicked_file {
-                            Some(x) => {
-                                let path = self.files[x].path().to_string_lossy().to_string();
-                                self.mv_target = Some(path);
-                            }
-                            None => {
-                                self.mv_target = None;
-                            }
-                        }
-                    }
+            Message::CancelJob(id) => {//the worker notices on its next chunk and reports back on the next tick
+                if let Some(job) = self.jobs.iter().find(|x| x.id == id) {
+                    job.cancel();
                 }
                 iced::Command::none()
             }
-            Message::CpClicked => {//do sanity checks then cp file
-                match self.cp_target {
-                    Some(..) => {
-                        self.cp_file();
-                    }
-                    None => {
-                        match self.last_clicked_file {
-                            Some(x) => {
-                                let path = self.files[x].path().to_string_lossy().to_string();
-                                self.cp_target = Some(path);
-                            }
-                            None => {
-                                self.cp_target = None;
-                            }
-                        }
-                    }
+            Message::ResolveConflict(policy) => {
+                self.resolve_conflict(policy);
+                iced::Command::none()
+            }
+            Message::ApplyToAllToggled(x) => {
+                if let Some(pending) = &mut self.pending_transfer {
+                    pending.apply_all = x;
                 }
                 iced::Command::none()
             }
-            Message::MkDir => {
-                self.mkdir();
+            Message::CancelTransfer => {//drop the transfer, nothing has been touched yet
+                self.pending_transfer = None;
+                iced::Command::none()
+            }
+            Message::ClearClipboard => {//forget about whatever was cut or copied
+                self.clipboard = None;
+                block_on(self.regen_ui_files());
                 iced::Command::none()
             }
+            Message::MkDir => {
+                self.mkdir()
+            }
             Message::MkFile => {
-                self.touch();
+                self.touch()
+            }
+            Message::TemplateMenuToggle => {
+                self.toggle_templates();
                 iced::Command::none()
             }
-            Message::RenameToggle => {
-                match &self.type_mode {
-                    Some(val) => {
-                        match self.last_clicked_file {
-                            Some(..) => {
-                                if val.len() >= 1 {
-                                    self.rename()
-                                }
-                                self.type_mode = None
-                            }
-                            None => {
-                                self.type_mode = None
-                            }
-                        }
-                        use cosmic_time::button;
-                        let un_mitosis = chain![RENAME_BTN,
-                            button(Duration::ZERO).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                            button(Duration::from_millis(500)).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                            button(Duration::from_millis(500)).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                        ];
-                        self.anims.set_chain(un_mitosis).start();
+            Message::TemplateChosen(x) => {
+                self.create_from_template(x)
+            }
+            Message::RenameToggle => {//the rename button opens the editor on the cursor, or applies it when it is already open
+                match self.type_mode {
+                    Some(..) => {
+                        self.finish_rename(true);
                         iced::Command::none()
-                    },
-                    None => {
-                        use cosmic_time::button;
-                        let mitosis = chain![RENAME_BTN,
-                            button(Duration::ZERO).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                            button(Duration::from_millis(500)).width(Length::Fixed(1000.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                            button(Duration::from_millis(500)).width(Length::Fixed(0.0)).height(Length::Fixed(TOP_HEIGHT as f32)),
-                        ];
-                        self.anims.set_chain(mitosis).start();
-                        self.type_mode = Some(String::default());
-                        text_input::focus(self.rename_id.clone())
                     }
+                    None => self.start_rename(),
                 }
             }
             Message::RenameUpdate(x) => {
                 self.type_mode = Some(x);
                 iced::Command::none()
             }
+            Message::BatchRenameOpen => {
+                self.open_batch_rename();
+                iced::Command::none()
+            }
+            Message::BatchRenameEdit(field, value) => {
+                if let Some(batch) = &mut self.batch_rename {
+                    batch.set_field(field, value);
+                }
+                iced::Command::none()
+            }
+            Message::BatchRenameRegex(x) => {
+                if let Some(batch) = &mut self.batch_rename {
+                    batch.use_regex = x;
+                }
+                iced::Command::none()
+            }
+            Message::BatchRenameKeepExt(x) => {
+                if let Some(batch) = &mut self.batch_rename {
+                    batch.keep_ext = x;
+                }
+                iced::Command::none()
+            }
+            Message::BatchRenameCase => {//cycle through the case transforms
+                if let Some(batch) = &mut self.batch_rename {
+                    batch.case = next_case(batch.case);
+                }
+                iced::Command::none()
+            }
+            Message::BatchRenameApply => {
+                self.apply_batch_rename();
+                iced::Command::none()
+            }
+            Message::EditorClosed(result) => {
+                self.apply_edited_names(result);
+                iced::Command::none()
+            }
+            Message::PropertiesOpen => {
+                self.open_properties();
+                iced::Command::none()
+            }
+            Message::PropertiesEdit(field, value) => {
+                if let Some(properties) = &mut self.properties {
+                    properties.set_field(field, value);
+                }
+                iced::Command::none()
+            }
+            Message::PropertiesBit(bit, on) => {
+                if let Some(properties) = &mut self.properties {
+                    properties.toggle_bit(bit, on);
+                }
+                iced::Command::none()
+            }
+            Message::PropertiesRecursive(x) => {
+                if let Some(properties) = &mut self.properties {
+                    properties.recursive = x;
+                }
+                iced::Command::none()
+            }
+            Message::PropertiesApply => {
+                self.apply_properties();
+                iced::Command::none()
+            }
+            Message::PropertiesClose => {
+                self.properties = None;
+                self.update_size_scan();
+                iced::Command::none()
+            }
+            Message::CompressClicked => {
+                self.open_compress()
+            }
+            Message::ExtractHere => {
+                self.extract_here();
+                iced::Command::none()
+            }
+            Message::ExtractTo => {
+                self.open_extract_to()
+            }
+            Message::ArchivePromptUpdate(x) => {
+                if let Some(prompt) = &mut self.archive_prompt {
+                    prompt.target = x;
+                }
+                iced::Command::none()
+            }
+            Message::ArchivePromptConfirm => {
+                self.confirm_archive_prompt();
+                iced::Command::none()
+            }
+            Message::ArchivePromptCancel => {
+                self.archive_prompt = None;
+                iced::Command::none()
+            }
+            Message::BatchRenameCancel => {
+                self.batch_rename = None;
+                iced::Command::none()
+            }
+            Message::CursorMoved(position) => {
+                self.cursor_position = Some(position);
+                self.follow_drag(position);
+                self.update_drop_target();
+                iced::Command::none()
+            }
+            Message::DropFinished => {//shift moves instead of copying
+                if self.dropped_at.map(|x| x.elapsed() < DROP_SETTLE).unwrap_or(true) {//more files of the same drop may still be on their way
+                    return iced::Command::none();
+                }
+                self.dropped_at = None;
+                let paths = std::mem::take(&mut self.dropped);
+                self.update_drop_target();
+                self.drop_files(paths, self.modifiers.shift());
+                self.drop_hover = vec![];
+                self.drop_target = None;
+                self.cursor_position = None;
+                block_on(self.regen_ui_files());
+                iced::Command::none()
+            }
             Message::Tick(now) => {
                 self.anims.now(now);
                 iced::Command::none()
@@ -622,7 +2123,13 @@ impl Application for Narwhal {
                 self.anims.start();
                 iced::Command::none()
             }
+        };
+        if self.location() != before {//somewhere new starts at the top of the grid
+            self.grid_offset = 0.0;
+            block_on(self.regen_ui_files());
+            return iced::Command::batch([command, scrollable::scroll_to(self.grid_id.clone(), scrollable::AbsoluteOffset { x: 0.0, y: 0.0 })]);
         }
+        command
     }
     fn view(&self) -> iced::Element<'_, Self::Message, iced::Renderer<Self::Theme>> {//render code!
         let current_theme = match self.theme {//clone selected theme into current_theme
@@ -631,25 +2138,24 @@ impl Application for Narwhal {
             SelectedTheme::Custom => self.themes.custom.clone(),
         }; 
         let translated = match self.show_keybinds {
-            true => [tr("<Backspace>"), tr("<S>"), tr("<Shift+Minus>"), tr("<M>"), tr("<M>"), tr("<C>"), tr("<C>"), tr("<H>"),  tr("<Shift+B>"), tr("<N>"), tr("<Shift+N>"), tr("<R>")],
-            false => [tr("Back"), tr("Sort"), tr("Delete"), tr("Move Here"), tr("Move"), tr("Paste"), tr("Copy"), tr("Hidden"), tr("Bookmark"), tr("Make File"), tr("Make Folder"), tr("Rename")]
+            true => [tr("<Backspace>"), tr("<S>"), tr("<Shift+Minus>"), tr("<Ctrl+X>"), tr("<Ctrl+C>"), tr("<Ctrl+V>"), tr("<Escape>"), tr("<H>"),  tr("<Shift+B>"), tr("<N>"), tr("<Shift+N>"), tr("<R>"), tr("<Shift+Delete>"), tr("<T>"), tr("<U>"), tr("Empty Trash"), tr("<Ctrl+R>"), tr("<Ctrl+N>"), tr("<Alt+Enter>"), tr("<Shift+C>"), tr("<E>"), tr("<Shift+E>"), tr("<Alt+Left>"), tr("<Alt+Right>"), tr("<Ctrl+T>"), tr("<F3>"), tr("<F5>"), tr("<F6>"), tr("<Shift+V>")],
+            false => [tr("Back"), tr("Sort"), tr("Trash"), tr("Cut"), tr("Copy"), tr("Paste"), tr("Clear"), tr("Hidden"), tr("Bookmark"), tr("Make File"), tr("Make Folder"), tr("Rename"), tr("Delete"), tr("Trash"), tr("Restore"), tr("Empty Trash"), tr("Batch Rename"), tr("From Template"), tr("Properties"), tr("Compress…"), tr("Extract Here"), tr("Extract To…"), tr("Previous"), tr("Next"), tr("New Tab"), tr("Split View"), tr("Copy Across"), tr("Move Across"), tr("Switch View")]
         };
         // construct top bar
         let option_btn = Button::new("...").height(TOP_HEIGHT).on_press(Message::ToggleMenu).style(current_theme.secondary.mk_theme());
         let back_btn = string_button(translated[0].clone(), SPECIAL_FONT_SIZE).on_press(Message::GoBack(1)).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
         let sort_btn = anim!(MENU_BTN, &self.anims, Text::new(translated[1].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::SortChanged).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
-        let delete_btn = if self.deletion_confirmation {
-            anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[2].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::DeleteClicked).height(TOP_HEIGHT).style(theme::Button::Destructive)
+        let delete_btn = anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[2].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::DeleteClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
+        let perm_delete_btn = if self.deletion_confirmation {
+            anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[12].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::PermDeleteClicked).height(TOP_HEIGHT).style(theme::Button::Destructive)
         } else {
-            anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[2].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::DeleteClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())
+            anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[12].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::PermDeleteClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())
         };
-        let mv_btn = match self.mv_target {
-            Some(..) => anim!(MENU_BTN, &self.anims, Text::new(translated[3].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::MvClicked),
-            None => anim!(MENU_BTN, &self.anims, Text::new(translated[4].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::MvClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())
-        };
-        let cp_btn = match self.cp_target {
-            Some(..) => anim!(MENU_BTN, &self.anims, Text::new(translated[5].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::CpClicked),
-            None => anim!(MENU_BTN, &self.anims, Text::new(translated[6].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::CpClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())
+        let cut_btn = anim!(MENU_BTN, &self.anims, Text::new(translated[3].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::CutClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
+        let cp_btn = anim!(MENU_BTN, &self.anims, Text::new(translated[4].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::CopyClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
+        let paste_btn = match self.clipboard {//paste stands out while something is on the clipboard
+            Some(..) => anim!(MENU_BTN, &self.anims, Text::new(translated[5].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::PasteClicked).height(TOP_HEIGHT),
+            None => anim!(MENU_BTN, &self.anims, Text::new(translated[5].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::PasteClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())
         };
         let hidden_btn = anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[7].clone()).size(SPECIAL_FONT_SIZE)).height(TOP_HEIGHT).on_press(Message::HiddenChanged).style(current_theme.secondary.mk_theme());
         let bookmark_btn = anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[8].clone()).size(SPECIAL_FONT_SIZE)).height(TOP_HEIGHT).on_press(Message::BookmarkCurrent).style(current_theme.secondary.mk_theme());
@@ -657,47 +2163,115 @@ impl Application for Narwhal {
         let mkdir_btn = anim!(MENU_BTN, &self.anims, Text::new(translated[10].clone()).size(SPECIAL_FONT_SIZE)).width(SIDEBAR_WIDTH).on_press(Message::MkDir).style(current_theme.sidebar.mk_theme());
         //let function_cap = Button::new("").width(5000).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
         let rename_btn = string_button(translated[11].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::RenameToggle).style(current_theme.secondary.mk_theme());
-        let function_cap = anim!(RENAME_BTN, &self.anims, "").height(TOP_HEIGHT).on_press(Message::NoOp).style(current_theme.secondary.mk_theme());
-        let mut function_buttons = Row::new().push(back_btn).push(option_btn).push(sort_btn).push(hidden_btn).push(bookmark_btn).push(delete_btn).push(mv_btn).push(cp_btn).push(rename_btn).push(function_cap);
-        let txt = match &self.type_mode {
-            Some(x) => x.clone(),
-            None => String::from("")
+        let batch_rename_btn = string_button(translated[16].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::BatchRenameOpen).style(current_theme.secondary.mk_theme());
+        let properties_btn = string_button(translated[18].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::PropertiesOpen).style(current_theme.secondary.mk_theme());
+        let compress_btn = string_button(translated[19].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::CompressClicked).style(current_theme.secondary.mk_theme());
+        let extract_here_btn = string_button(translated[20].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::ExtractHere).style(current_theme.secondary.mk_theme());
+        let extract_to_btn = string_button(translated[21].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::ExtractTo).style(current_theme.secondary.mk_theme());
+        let split_btn = string_button(translated[25].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::SplitToggled).style(current_theme.secondary.mk_theme());
+        let copy_across_btn = string_button(translated[26].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::CopyAcross).style(current_theme.secondary.mk_theme());
+        let move_across_btn = string_button(translated[27].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::MoveAcross).style(current_theme.secondary.mk_theme());
+        let view_btn = string_button(translated[28].clone(), SPECIAL_FONT_SIZE).height(Length::Fixed(TOP_HEIGHT as f32)).on_press(Message::ViewToggled).style(current_theme.secondary.mk_theme());
+        let function_cap = Button::new("").height(TOP_HEIGHT).width(Length::Fill).on_press(Message::NoOp).style(current_theme.secondary.mk_theme());
+        let function_buttons = if self.trash_view {//the trash only supports restoring and deleting for good
+            let restore_btn = anim!(MENU_BTN_LONG, &self.anims, Text::new(translated[14].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::RestoreClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
+            let empty_btn = if self.empty_confirmation {
+                string_button(translated[15].clone(), SPECIAL_FONT_SIZE).on_press(Message::EmptyTrashClicked).height(TOP_HEIGHT).style(theme::Button::Destructive)
+            } else {
+                string_button(translated[15].clone(), SPECIAL_FONT_SIZE).on_press(Message::EmptyTrashClicked).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())
+            };
+            Row::new().push(back_btn).push(option_btn).push(sort_btn).push(hidden_btn).push(restore_btn).push(perm_delete_btn).push(empty_btn)
+        } else {
+            Row::new().push(back_btn).push(option_btn).push(sort_btn).push(hidden_btn).push(bookmark_btn).push(delete_btn).push(perm_delete_btn).push(cut_btn).push(cp_btn).push(paste_btn).push(rename_btn).push(batch_rename_btn).push(properties_btn).push(compress_btn).push(extract_here_btn).push(extract_to_btn)
+        };
+        let function_buttons = match (&self.split, self.trash_view) {//copying and moving across only make sense with a second pane to go to
+            (Some(..), false) => function_buttons.push(copy_across_btn).push(move_across_btn).push(split_btn).push(view_btn).push(function_cap),
+            _ => function_buttons.push(split_btn).push(view_btn).push(function_cap),
         };
-        let rename_input = TextInput::new(tr("Placeholder").as_str(), txt.as_str()).on_input(Message::RenameUpdate).size(SPECIAL_FONT_SIZE).id(self.rename_id.clone());
-        function_buttons = function_buttons.push(rename_input);
+        //construct tab strip
+        let mut tab_strip = Row::new();
+        for (i, tab) in self.tabs.iter().enumerate() {
+            let title = match i == self.active_tab {//the active tab's copy in tabs is stale, its title comes from the live state
+                true => self.location().title(),
+                false => tab.location().title(),
+            };
+            let label = Text::new(clip_file_name(title)).size(SPECIAL_FONT_SIZE);
+            let close_label = Text::new("×").size(SPECIAL_FONT_SIZE);
+            match i == self.active_tab {
+                true => tab_strip = tab_strip.push(Button::new(label).on_press(Message::TabSelected(i)).height(TOP_HEIGHT)).push(Button::new(close_label).on_press(Message::TabClosed(i)).height(TOP_HEIGHT)),
+                false => tab_strip = tab_strip.push(Button::new(label).on_press(Message::TabSelected(i)).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())).push(Button::new(close_label).on_press(Message::TabClosed(i)).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme())),
+            }
+        }
+        let new_tab_btn = string_button(translated[24].clone(), SPECIAL_FONT_SIZE).on_press(Message::NewTab).height(TOP_HEIGHT).style(current_theme.secondary.mk_theme());
+        let tab_cap = Button::new("").height(TOP_HEIGHT).width(Length::Fill).on_press(Message::NoOp).style(current_theme.secondary.mk_theme());
+        tab_strip = tab_strip.push(new_tab_btn).push(tab_cap);
         //construct bookmark column
-        let mut bookmark_buttons = Column::new().push(mkdir_btn).push(touch_btn);
+        let template_btn = Button::new(Text::new(translated[17].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::TemplateMenuToggle).width(SIDEBAR_WIDTH).height(TOP_HEIGHT).style(current_theme.sidebar.mk_theme());
+        let mut bookmark_buttons = Column::new().push(mkdir_btn).push(touch_btn).push(template_btn);
+        if let Some(templates) = &self.templates {//template submenu, right under the button that opened it
+            for (i, (path, icon)) in templates.iter().enumerate() {
+                let name = match self.show_keybinds && i < 10 {
+                    true => format!("<{}>", (i + 1) % 10),
+                    false => clip_file_name(path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default()),
+                };
+                let entry = Row::new().spacing(4).align_items(iced::Alignment::Center)
+                    .push(svg(svg::Handle::from_path(icon)).width(FONT_SIZE + 4).height(FONT_SIZE + 4))
+                    .push(Text::new(name).size(FONT_SIZE));
+                bookmark_buttons = bookmark_buttons.push(Button::new(entry).on_press(Message::TemplateChosen(i)).width(SIDEBAR_WIDTH).height(TOP_HEIGHT).style(current_theme.sidebar.mk_theme()));
+            }
+        }
         for i in 0..self.bookmarked_dirs.len() {
             let btn_text = match self.show_keybinds {
                 false => Text::new(self.bookmarked_dirs[i].name.clone()).size(SPECIAL_FONT_SIZE),
                 true => Text::new(format!("<{}>", i+1)).size(SPECIAL_FONT_SIZE),
             };
-            let btn = Button::new(btn_text).on_press(Message::BookmarkClicked(i)).width(SIDEBAR_WIDTH).style(current_theme.sidebar.mk_theme());
+            let btn = match self.drop_target == Some(DropTarget::Bookmark(i)) {//light up the bookmark something would be dropped into
+                true => Button::new(btn_text).on_press(Message::BookmarkClicked(i)).width(SIDEBAR_WIDTH).height(TOP_HEIGHT).style(theme::Button::Positive),
+                false => Button::new(btn_text).on_press(Message::BookmarkClicked(i)).width(SIDEBAR_WIDTH).height(TOP_HEIGHT).style(current_theme.sidebar.mk_theme()),
+            };
             bookmark_buttons = bookmark_buttons.push(btn);
         }
+        let trash_btn = Button::new(Text::new(translated[13].clone()).size(SPECIAL_FONT_SIZE)).on_press(Message::TrashClicked).width(SIDEBAR_WIDTH).height(TOP_HEIGHT).style(current_theme.sidebar.mk_theme());
+        bookmark_buttons = bookmark_buttons.push(trash_btn);
         let bookmark_cap = Button::new("").height(5000).width(SIDEBAR_WIDTH).style(current_theme.sidebar.mk_theme()).on_press(Message::NoOp);
         bookmark_buttons = bookmark_buttons.push(bookmark_cap);
         //construct file view
-        let mut file_listing = Column::new();
-        let mut temp_row = Row::new();
         let mut file_btn_futures = vec![];
+        let rename_problem = match self.rename_check() {//the tile is narrow, keep the message short
+            Ok(..) => None,
+            Err(FileOpError::AlreadyExists(..)) => Some(tr("That name is taken")),
+            Err(e) => Some(e.to_string()),
+        };
         for i in 0..self.ui_files.len() {
-            file_btn_futures.push(self.ui_files[i].render());
+            let editing = match (&self.type_mode, self.selection.cursor) {//the tile under the cursor turns into the rename editor
+                (Some(value), Some(cursor)) if cursor == self.ui_files[i].original_index => Some((value.clone(), self.rename_id.clone(), rename_problem.clone())),
+                _ => None,
+            };
+            file_btn_futures.push(self.ui_files[i].render(editing));
         }
-        let mut test = block_on(join_all(file_btn_futures));
-        for i in 0..test.len() {
-            let full = test.remove(0);
-            if i % self.desired_cols as usize == 0 {
-                file_listing = file_listing.push(temp_row);
-                temp_row = Row::new().spacing(SPACING);
+        let total_rows = (self.visible_indexes().len() + self.desired_cols as usize - 1) / self.desired_cols as usize;
+        let file_listing = Column::new()//rows without tiles are stood in for by empty space, so the scrollbar still covers every entry
+            .push(Space::with_height((self.ui_start_row * EST_HEIGHT as usize) as f32))
+            .push(grid(block_on(join_all(file_btn_futures)), self.desired_cols))
+            .push(Space::with_height((total_rows.saturating_sub(self.ui_end_row) * EST_HEIGHT as usize) as f32));
+        let file_listing = Scrollable::new(file_listing).id(self.grid_id.clone()).on_scroll(Message::GridScrolled).height(Length::Fill);
+        let file_listing: iced::Element<'_, Self::Message, iced::Renderer<Self::Theme>> = match &self.split {//the pane without focus only reacts to a press by taking focus
+            _ if self.view_mode == ViewMode::Miller => self.miller_columns(),//the Miller view only ever shows the focused pane
+            Some(pane) => {
+                let other_tiles = block_on(join_all(pane.ui_files.iter().map(|x| x.render(None)))).into_iter().map(|x| x.on_press(Message::PaneFocused).on_middle_press(Message::PaneFocused)).collect();
+                let other_listing = Container::new(grid(other_tiles, self.desired_cols)).width((self.desired_cols * EST_LENGTH) as f32).style(theme::Container::Box);
+                let file_listing = file_listing.width((self.desired_cols * EST_LENGTH) as f32);
+                match self.focus_right {
+                    true => Row::new().push(other_listing).push(Rule::vertical(RULE_WIDTH)).push(file_listing).into(),
+                    false => Row::new().push(file_listing).push(Rule::vertical(RULE_WIDTH)).push(other_listing).into(),
+                }
             }
-            temp_row = temp_row.push(full);
-        }
-        file_listing = file_listing.push(temp_row);
+            None => file_listing.into(),
+        };
         let mut path_bar = Row::new();
-        let chars: Vec<char> = self.current_path.to_string_lossy().to_string().chars().collect();
+        let chars: Vec<char> = self.shown_path().to_string_lossy().to_string().chars().collect();
         let mut path_entries = vec![];
-        let path_cap = Button::new("").height(TOP_HEIGHT).width(10000).style(current_theme.sidebar.mk_theme()).on_press(Message::NoOp);
+        let path_cap = Button::new("").height(TOP_HEIGHT).width(10000).style(current_theme.sidebar.mk_theme()).on_press(Message::LocationEditOpen);//clicking past the breadcrumbs edits the path as text
         let mut entries = 0;
         for character in chars {
             if character == '/' {
@@ -708,18 +2282,263 @@ impl Application for Narwhal {
             }
         }
         let mut iterations = 0;
-        for entry in path_entries {
-            path_bar = path_bar.push(Button::new(Text::new("/")).on_press(Message::NoOp).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT));
-            path_bar = path_bar.push(Button::new(Text::new(entry)).on_press(Message::GoBack(entries - iterations)).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT));
-            iterations = iterations + 1;
+        let here = self.location();
+        let previous_btn = match self.history.back.is_empty() {//history buttons and the recent locations dropdown lead the path bar
+            true => string_button(translated[22].clone(), FONT_SIZE).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT),
+            false => string_button(translated[22].clone(), FONT_SIZE).on_press(Message::HistoryBack).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT),
+        };
+        let next_btn = match self.history.forward.is_empty() {
+            true => string_button(translated[23].clone(), FONT_SIZE).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT),
+            false => string_button(translated[23].clone(), FONT_SIZE).on_press(Message::HistoryForward).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT),
+        };
+        let recent = PickList::new(self.history.recent(&here), None, Message::HistoryChosen).placeholder(tr("Recent")).text_size(FONT_SIZE).width(SIDEBAR_WIDTH);
+        path_bar = path_bar.push(previous_btn).push(next_btn).push(recent);
+        if self.trash_view {//the trash has no real path, show it as a single entry
+            path_bar = path_bar.push(Button::new(Text::new(translated[13].clone())).on_press(Message::TrashClicked).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT));
+        } else {
+            for entry in path_entries {
+                path_bar = path_bar.push(Button::new(Text::new("/")).on_press(Message::NoOp).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT));
+                path_bar = path_bar.push(Button::new(Text::new(entry)).on_press(Message::GoBack(entries - iterations)).style(current_theme.sidebar.mk_theme()).height(TOP_HEIGHT));
+                iterations = iterations + 1;
+            }
         }
         path_bar = path_bar.push(path_cap);
+        if let Some(edit) = &self.location_edit {//typing a path takes the place of the breadcrumbs, with any problem right next to it
+            path_bar = Row::new().spacing(SPACING).align_items(iced::Alignment::Center).push(TextInput::new(tr("Type a path").as_str(), &edit.value).on_input(Message::LocationEdited).on_submit(Message::LocationSubmitted).id(self.location_id.clone()).size(SPECIAL_FONT_SIZE));
+            if let Some(err) = &edit.error {
+                path_bar = path_bar.push(Text::new(err.clone()).size(FONT_SIZE).style(current_theme.application.danger));
+            }
+        }
         //return render commands
         let rule_h = Rule::horizontal(RULE_WIDTH);
         let rule_h2 = Rule::horizontal(RULE_WIDTH);
         let fill_space = Space::new(10, Length::Fill);
         let rule_v = Rule::vertical(RULE_WIDTH);
-        let col_test = Column::new().push(function_buttons).push(rule_h).push(file_listing).push(fill_space).push(rule_h2).push(path_bar);
+        let main_view: iced::Element<'_, Self::Message, iced::Renderer<Self::Theme>> = match &self.batch_rename {//the batch rename dialog takes the place of the grid while it is open
+            Some(batch) => {
+                let case_text = match batch.case {
+                    rename_helpers::CaseMode::Keep => tr("Keep Case"),
+                    rename_helpers::CaseMode::Lower => tr("lowercase"),
+                    rename_helpers::CaseMode::Upper => tr("UPPERCASE"),
+                    rename_helpers::CaseMode::Title => tr("Title Case"),
+                };
+                let fields = Column::new().spacing(SPACING / 2)
+                    .push(Text::new(format!("{} {} {}", tr("Renaming"), batch.paths.len(), tr("items"))).size(SPECIAL_FONT_SIZE))
+                    .push(Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                        .push(TextInput::new(tr("Find").as_str(), &batch.find).on_input(|x| Message::BatchRenameEdit(BatchField::Find, x)).size(FONT_SIZE))
+                        .push(TextInput::new(tr("Replace with").as_str(), &batch.replace).on_input(|x| Message::BatchRenameEdit(BatchField::Replace, x)).size(FONT_SIZE))
+                        .push(Checkbox::new(tr("Regex"), batch.use_regex, Message::BatchRenameRegex).size(FONT_SIZE).text_size(FONT_SIZE)))
+                    .push(Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                        .push(TextInput::new("{name}", &batch.template).on_input(|x| Message::BatchRenameEdit(BatchField::Template, x)).size(FONT_SIZE))
+                        .push(Checkbox::new(tr("Keep extension"), batch.keep_ext, Message::BatchRenameKeepExt).size(FONT_SIZE).text_size(FONT_SIZE))
+                        .push(string_button(case_text, FONT_SIZE).on_press(Message::BatchRenameCase).style(current_theme.secondary.mk_theme())))
+                    .push(Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                        .push(Text::new(tr("Start at")).size(FONT_SIZE))
+                        .push(TextInput::new("1", &batch.start).on_input(|x| Message::BatchRenameEdit(BatchField::Start, x)).size(FONT_SIZE).width(60))
+                        .push(Text::new(tr("Digits")).size(FONT_SIZE))
+                        .push(TextInput::new("1", &batch.padding).on_input(|x| Message::BatchRenameEdit(BatchField::Padding, x)).size(FONT_SIZE).width(60))
+                        .push(Text::new(tr("Tokens: {name} {ext} {n} {date} {date:%Y%m%d}")).size(FONT_SIZE)));
+                let mut preview_list = Column::new().spacing(2);
+                let mut can_apply = true;
+                match batch.preview() {
+                    Ok(previews) => {
+                        for preview in previews {
+                            let old_name = preview.source.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+                            let line = Text::new(format!("{} → {}", old_name, preview.new_name)).size(FONT_SIZE);
+                            match preview.problem {
+                                Some(problem) => {
+                                    can_apply = false;
+                                    preview_list = preview_list.push(Row::new().spacing(SPACING).push(line.style(current_theme.application.danger)).push(Text::new(problem).size(FONT_SIZE).style(current_theme.application.danger)));
+                                }
+                                None => preview_list = preview_list.push(line),
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        can_apply = false;
+                        preview_list = preview_list.push(Text::new(e).size(FONT_SIZE).style(current_theme.application.danger));
+                    }
+                }
+                let apply_btn = match can_apply {//only clickable once every name checks out
+                    true => string_button(tr("Apply"), FONT_SIZE).on_press(Message::BatchRenameApply),
+                    false => string_button(tr("Apply"), FONT_SIZE).style(current_theme.secondary.mk_theme()),
+                };
+                let actions = Row::new().spacing(SPACING).push(apply_btn).push(string_button(tr("Cancel"), FONT_SIZE).on_press(Message::BatchRenameCancel).style(current_theme.secondary.mk_theme()));
+                Column::new().spacing(SPACING).padding(SPACING).push(fields).push(Scrollable::new(preview_list).height(Length::Fill)).push(actions).into()
+            }
+            None => match &self.properties {//so does the properties panel
+                Some(properties) => {
+                    let label = |x: String| Text::new(x).size(FONT_SIZE).width(100);
+                    let line = |name: String, value: String| Row::new().spacing(SPACING).push(label(name)).push(Text::new(value).size(FONT_SIZE));
+                    let size = match properties.entries {
+                        Some(x) => match self.size_summary() {//the folder's full size streams in as it is counted
+                            Some(summary) => format!("{} {} · {}", x, tr("items"), summary),
+                            None => format!("{} {}", x, tr("items")),
+                        },
+                        None => format!("{} ({} B)", format_size(properties.size), properties.size),
+                    };
+                    let mut info = Column::new().spacing(SPACING / 2)
+                        .push(Text::new(properties.path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default()).size(SPECIAL_FONT_SIZE))
+                        .push(line(tr("Location"), properties.path.parent().map(|x| x.to_string_lossy().to_string()).unwrap_or_default()))
+                        .push(line(tr("Type"), properties.mime.clone()));
+                    if let Some(target) = &properties.link_target {
+                        info = info.push(line(tr("Links to"), target.clone()));
+                    }
+                    info = info.push(line(tr("Size"), size));
+                    if let Some(created) = properties.created {
+                        info = info.push(line(tr("Created"), format_time(created)));
+                    }
+                    let field = |name: String, which: PropField, value: &str| Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                        .push(label(name))
+                        .push(TextInput::new("", value).on_input(move |x| Message::PropertiesEdit(which, x)).size(FONT_SIZE).width(200));
+                    info = info
+                        .push(field(tr("Modified"), PropField::Modified, &properties.modified_text))
+                        .push(field(tr("Accessed"), PropField::Accessed, &properties.accessed_text))
+                        .push(field(tr("Owner"), PropField::Owner, &properties.owner_text))
+                        .push(field(tr("Group"), PropField::Group, &properties.group_text));
+                    let mode = properties.typed_mode().unwrap_or(properties.mode);
+                    let mut permissions = Column::new().spacing(4);
+                    for (who, shift) in [(tr("Owner"), 6), (tr("Group"), 3), (tr("Others"), 0)] {//one row of rwx checkboxes per class
+                        let mut row = Row::new().spacing(SPACING).push(label(who));
+                        for (what, bit) in [(tr("Read"), 0o4), (tr("Write"), 0o2), (tr("Execute"), 0o1)] {
+                            let bit = bit << shift;
+                            row = row.push(Checkbox::new(what, mode & bit != 0, move |x| Message::PropertiesBit(bit, x)).size(FONT_SIZE).text_size(FONT_SIZE));
+                        }
+                        permissions = permissions.push(row);
+                    }
+                    permissions = permissions.push(Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                        .push(label(tr("Octal")))
+                        .push(TextInput::new("644", &properties.mode_text).on_input(|x| Message::PropertiesEdit(PropField::Mode, x)).size(FONT_SIZE).width(60))
+                        .push(Text::new(mode_string(mode)).size(FONT_SIZE)));
+                    if properties.is_dir {
+                        permissions = permissions.push(Checkbox::new(tr("Apply to everything inside too"), properties.recursive, Message::PropertiesRecursive).size(FONT_SIZE).text_size(FONT_SIZE));
+                    }
+                    let was = format!("{} {}:{} {}", tr("Currently"), user_name(properties.uid), group_name(properties.gid), mode_string(properties.mode));
+                    let actions = Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                        .push(match self.read_only() {//shown but not editable in the trash or an archive
+                            true => string_button(tr("Apply"), FONT_SIZE).style(current_theme.secondary.mk_theme()),
+                            false => string_button(tr("Apply"), FONT_SIZE).on_press(Message::PropertiesApply),
+                        })
+                        .push(string_button(tr("Close"), FONT_SIZE).on_press(Message::PropertiesClose).style(current_theme.secondary.mk_theme()))
+                        .push(Text::new(was).size(FONT_SIZE));
+                    Column::new().spacing(SPACING).padding(SPACING).push(info).push(permissions).push(actions).into()
+                }
+                None => file_listing,
+            },
+        };
+        let mut col_test = Column::new().push(tab_strip).push(function_buttons).push(rule_h).push(main_view);
+        if self.batch_rename.is_none() && self.properties.is_some() {//the grid and the Miller columns fill the space themselves
+            col_test = col_test.push(fill_space);
+        }
+        if self.trash_view {//show where the selected trash entry came from and when it was deleted
+            let info = match self.selection.cursor.and_then(|x| self.trash_entries.get(&self.files[x].path())) {
+                Some(entry) => format!("{} {} · {} {}", tr("Originally at"), entry.original_path.to_string_lossy(), tr("deleted"), entry.deletion_date.replace('T', " ")),
+                None => format!("{} {}", self.files.len(), tr("items in the trash")),
+            };
+            col_test = col_test.push(Text::new(info).size(FONT_SIZE));
+        }
+        for job in &self.jobs {//jobs panel, one row per running job
+            let progress = &job.progress;
+            let items = format!("{}/{} {}", progress.items_done.load(Ordering::Relaxed), progress.items_total.load(Ordering::Relaxed), tr("items"));
+            let bytes = format!("{}/{}", format_size(progress.bytes_done.load(Ordering::Relaxed)), format_size(progress.bytes_total.load(Ordering::Relaxed)));
+            let eta = match job.eta() {
+                Some(x) => format!("{} {}s", tr("ETA"), x.as_secs()),
+                None => String::new(),
+            };
+            let paused = progress.paused.load(Ordering::Relaxed);
+            let pause_text = match paused {
+                true => tr("Resume"),
+                false => tr("Pause"),
+            };
+            let stats = format!("{} · {} · {}/s · {}", items, bytes, format_size(job.throughput() as u64), eta);
+            let details = Column::new().width(Length::Fill)
+                .push(Text::new(format!("{} — {}", job.title, progress.current())).size(FONT_SIZE))
+                .push(ProgressBar::new(0.0..=1.0, job.fraction()).height(8))
+                .push(Text::new(stats).size(FONT_SIZE));
+            let job_row = Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                .push(details)
+                .push(string_button(pause_text, FONT_SIZE).on_press(Message::PauseJob(job.id)).style(current_theme.secondary.mk_theme()))
+                .push(string_button(tr("Cancel"), FONT_SIZE).on_press(Message::CancelJob(job.id)).style(theme::Button::Destructive));
+            col_test = col_test.push(job_row);
+        }
+        if let Some(clipboard) = &self.clipboard {//show what is waiting to be pasted
+            let action = match clipboard.cut {
+                true => tr("cut"),
+                false => tr("copied"),
+            };
+            let clipboard_row = Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                .push(Text::new(format!("{} {} {}", clipboard.paths.len(), tr("items"), action)).size(FONT_SIZE))
+                .push(string_button(translated[6].clone(), FONT_SIZE).on_press(Message::ClearClipboard).style(current_theme.secondary.mk_theme()));
+            col_test = col_test.push(clipboard_row);
+        }
+        if let Some(pending) = &self.pending_transfer {//conflict prompt, one destination at a time
+            let item = &pending.items[pending.conflicts[0]];
+            let name = item.dest.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+            let dir = item.dest.parent().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+            let mut prompt = Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                .push(Text::new(format!("{}: \"{}\" {} {}", pending.title, name, tr("already exists in"), dir)).size(FONT_SIZE))
+                .push(string_button(tr("Overwrite"), FONT_SIZE).on_press(Message::ResolveConflict(ConflictPolicy::Overwrite)).style(theme::Button::Destructive))
+                .push(string_button(tr("Skip"), FONT_SIZE).on_press(Message::ResolveConflict(ConflictPolicy::Skip)).style(current_theme.secondary.mk_theme()))
+                .push(string_button(tr("Keep Both"), FONT_SIZE).on_press(Message::ResolveConflict(ConflictPolicy::KeepBoth)).style(current_theme.secondary.mk_theme()));
+            if both_dirs(Path::new(&item.source), &item.dest) {
+                prompt = prompt.push(string_button(tr("Merge"), FONT_SIZE).on_press(Message::ResolveConflict(ConflictPolicy::Merge)).style(current_theme.secondary.mk_theme()));
+            }
+            if pending.conflicts.len() > 1 {
+                prompt = prompt.push(Checkbox::new(format!("{} ({})", tr("Apply to all"), pending.conflicts.len()), pending.apply_all, Message::ApplyToAllToggled).size(FONT_SIZE).text_size(FONT_SIZE));
+            }
+            prompt = prompt.push(string_button(tr("Cancel"), FONT_SIZE).on_press(Message::CancelTransfer).style(current_theme.secondary.mk_theme()));
+            col_test = col_test.push(prompt);
+        }
+        if self.drop_hover.len() > 0 {//drop indicator, saying where the hovering files would go
+            let action = match self.modifiers.shift() {
+                true => tr("Drop to move"),
+                false => tr("Drop to copy (hold Shift to move)"),
+            };
+            let text = match self.drop_dir() {
+                Some(dir) => format!("{} {} {} {}", action, self.drop_hover.len(), tr("items into"), dir.to_string_lossy()),
+                None => tr("Files can't be dropped here"),
+            };
+            col_test = col_test.push(Container::new(Text::new(text).size(SPECIAL_FONT_SIZE)).width(Length::Fill).padding(SPACING / 2).style(theme::Container::Box));
+        }
+        if let Some(drag) = self.drag.as_ref().filter(|x| x.moved) {//same for a tile dragged around inside the window
+            let action = match self.modifiers.control() || !self.archive_stack.is_empty() {
+                true => tr("Drop to copy"),
+                false => tr("Drop to move (hold Ctrl to copy)"),
+            };
+            let text = match (self.drop_target, self.drop_dir()) {
+                (Some(..), Some(dir)) => format!("{} {} {} {}", action, drag.paths.len(), tr("items into"), dir.to_string_lossy()),
+                _ => tr("Drag onto a folder or a bookmark"),
+            };
+            col_test = col_test.push(Container::new(Text::new(text).size(SPECIAL_FONT_SIZE)).width(Length::Fill).padding(SPACING / 2).style(theme::Container::Box));
+        }
+        if let Some(prompt) = &self.archive_prompt {//name the new archive, or pick where to extract to
+            let question = match prompt.compress {
+                true => format!("{} {} {}", tr("Compress"), prompt.paths.len(), tr("items into")),
+                false => format!("{} {} {}", tr("Extract"), prompt.paths.len(), tr("archives into")),
+            };
+            let placeholder = match prompt.compress {
+                true => tr("name.zip, .tar, .tar.gz or .tar.zst"),
+                false => tr("folder"),
+            };
+            let prompt_row = Row::new().spacing(SPACING).align_items(iced::Alignment::Center)
+                .push(Text::new(question).size(FONT_SIZE))
+                .push(TextInput::new(placeholder.as_str(), &prompt.target).id(self.prompt_id.clone()).on_input(Message::ArchivePromptUpdate).on_submit(Message::ArchivePromptConfirm).size(FONT_SIZE))
+                .push(string_button(tr("OK"), FONT_SIZE).on_press(Message::ArchivePromptConfirm))
+                .push(string_button(tr("Cancel"), FONT_SIZE).on_press(Message::ArchivePromptCancel).style(current_theme.secondary.mk_theme()));
+            col_test = col_test.push(prompt_row);
+        }
+        if let (None, Some(summary)) = (&self.properties, self.size_summary()) {//size of the selected folders, the properties panel shows its own
+            col_test = col_test.push(Text::new(format!("{}: {}", tr("Selected folders"), summary)).size(FONT_SIZE));
+        }
+        if let Some(err) = &self.op_error {//show the last failed operation above the path bar
+            col_test = col_test.push(Text::new(err.clone()).size(FONT_SIZE).style(current_theme.application.danger));
+        }
+        if let Some(edit) = &self.location_edit {//matching folders drop up from the path bar
+            for option in edit.matches.iter().take(COMPLETION_ROWS) {
+                col_test = col_test.push(Button::new(Text::new(option.clone()).size(FONT_SIZE)).on_press(Message::LocationChosen(option.clone())).width(Length::Fill).style(current_theme.sidebar.mk_theme()));
+            }
+        }
+        col_test = col_test.push(rule_h2).push(path_bar);
         let row_test = Row::new().push(bookmark_buttons).push(rule_v).push(col_test);
         Container::new(row_test).width(Length::Fill).height(Length::Fill).into()
     }
@@ -731,12 +2550,34 @@ impl Application for Narwhal {
         }
     }
     fn subscription(&self) -> iced::Subscription<Message> {//listen in on keyboard and window events
+        let counting = self.size_scan.as_ref().map(|x| !x.is_done()).unwrap_or(false);
+        let job_ticks = match self.jobs.len() > 0 || counting {//only poll while something runs in the background
+            false => iced::Subscription::none(),
+            true => iced::time::every(std::time::Duration::from_millis(250)).map(|_| Message::JobTick),
+        };
+        let drop_ticks = match self.dropped.is_empty() {//check back until a drop has settled
+            true => iced::Subscription::none(),
+            false => iced::time::every(DROP_SETTLE / 3).map(|_| Message::DropFinished),
+        };
+        let cursor_moves = match self.drop_hover.len() > 0 || self.drag.is_some() {//follow the cursor only while something is dragged, every move redraws the window
+            false => iced::Subscription::none(),
+            true => iced::subscription::events_with(|event, _| match event {
+                Event::Mouse(iced::mouse::Event::CursorMoved { position }) => Some(Message::CursorMoved(position)),
+                _ => None
+            }),
+        };
         iced::Subscription::batch(vec![
+            job_ticks,
+            drop_ticks,
+            cursor_moves,
             self.anims.as_subscription::<Event>().map(Message::Tick),
             iced::subscription::events_with(
                 |event, _| match event {
                     Event::Keyboard(evt) => Some(Message::KeyboardUpdate(evt)),
                     Event::Window(evt) => Some(Message::WindowUpdate(evt)),
+                    Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)) => Some(Message::DragReleased),//always listened for, a press and release in one batch would otherwise beat the drag listener to it
+                    Event::Mouse(iced::mouse::Event::ButtonPressed(iced::mouse::Button::Other(8 | 275))) => Some(Message::HistoryBack),//the side buttons, as numbered by X11 and by evdev under Wayland
+                    Event::Mouse(iced::mouse::Event::ButtonPressed(iced::mouse::Button::Other(9 | 276))) => Some(Message::HistoryForward),
                     _ => None
                 }
             )
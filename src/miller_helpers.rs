@@ -0,0 +1,62 @@
+use std::fs::{self, DirEntry, File};
+use std::io::Read;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use gettextrs::gettext as tr;
+use crate::config_helpers::SortType;
+use crate::icon_helpers::get_file_mimetype;
+use crate::sort_file_by_type;
+use crate::ui_helpers::format_size;
+
+const PREVIEW_BYTES: u64 = 4096;
+const PREVIEW_LINES: usize = 40;
+
+pub enum Preview {//what the third Miller column shows for the entry under the cursor
+    Listing(Vec<DirEntry>),
+    Text(String),
+    Info(String),//anything that can't be shown as text gets its type and size
+}
+
+pub fn read_listing(path: &Path, sort_type: SortType) -> Vec<DirEntry> {//every entry of a folder in the usual order, nothing if it can't be read
+    let mut output: Vec<DirEntry> = match fs::read_dir(path) {
+        Ok(x) => x.flatten().collect(),
+        Err(..) => vec![],
+    };
+    sort_file_by_type(&mut output, sort_type);
+    output
+}
+pub fn preview(path: &Path, sort_type: SortType) -> Preview {//folders list their contents, text files show their first lines
+    let metadata = match fs::metadata(path) {
+        Ok(x) => x,
+        Err(e) => return Preview::Info(e.to_string()),
+    };
+    if metadata.is_dir() {
+        return Preview::Listing(read_listing(path, sort_type));
+    }
+    let file_type = metadata.file_type();
+    if !metadata.is_file() {//opening a fifo blocks until someone writes to it and devices never run out, so only regular files are read
+        let kind = match () {
+            _ if file_type.is_fifo() => tr("Named pipe"),
+            _ if file_type.is_socket() => tr("Socket"),
+            _ if file_type.is_char_device() => tr("Character device"),
+            _ if file_type.is_block_device() => tr("Block device"),
+            _ => tr("Special file"),
+        };
+        return Preview::Info(kind);
+    }
+    let mut head = vec![];
+    if let Err(e) = File::open(path).and_then(|x| x.take(PREVIEW_BYTES).read_to_end(&mut head)) {
+        return Preview::Info(e.to_string());
+    }
+    match head.contains(&0) {//a NUL byte this early means it isn't text
+        true => Preview::Info(format!("{}\n{}", get_file_mimetype(path.to_string_lossy().to_string()), format_size(metadata.len()))),
+        false => Preview::Text(String::from_utf8_lossy(&head).lines().take(PREVIEW_LINES).collect::<Vec<&str>>().join("\n")),
+    }
+}
+pub fn entry_label(entry: &DirEntry) -> String {//folders get a trailing slash, like ranger
+    let name = entry.file_name().to_string_lossy().to_string();
+    match entry.file_type().map(|x| x.is_dir()).unwrap_or(false) {
+        true => format!("{name}/"),
+        false => name,
+    }
+}
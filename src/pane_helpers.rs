@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::fs::DirEntry;
+use std::path::PathBuf;
+use iced::Point;
+use crate::archive_helpers::ArchiveMount;
+use crate::config_helpers::SortType;
+use crate::history_helpers::History;
+use crate::selection_helpers::Selection;
+use crate::trash_helpers::TrashEntry;
+use crate::ui_helpers::UIFile;
+use crate::{EST_LENGTH, RULE_WIDTH, SIDEBAR_WIDTH, TOP_HEIGHT};
+
+pub struct Pane {//the pane without focus, the focused one lives in Narwhal's own fields and the two trade places on a switch
+    pub files: Vec<DirEntry>,
+    pub ui_files: Vec<UIFile>,
+    pub current_path: PathBuf,
+    pub sort_type: SortType,
+    pub show_hidden: bool,
+    pub trash_view: bool,
+    pub trash_entries: HashMap<PathBuf, TrashEntry>,
+    pub archive_stack: Vec<ArchiveMount>,
+    pub selection: Selection,
+    pub history: History,
+    pub grid_offset: f32,
+    pub ui_start_row: usize,
+    pub ui_end_row: usize,
+}
+
+impl Pane {
+    pub fn new(current_path: PathBuf, sort_type: SortType, show_hidden: bool) -> Pane {//an empty pane, the listing is filled in on the first refresh
+        Pane {
+            files: vec![],
+            ui_files: vec![],
+            current_path,
+            sort_type,
+            show_hidden,
+            trash_view: false,
+            trash_entries: HashMap::new(),
+            archive_stack: vec![],
+            selection: Selection::default(),
+            history: History::default(),
+            grid_offset: 0.0,
+            ui_start_row: 0,
+            ui_end_row: 0,
+        }
+    }
+    pub fn read_only(&self) -> bool {
+        self.trash_view || !self.archive_stack.is_empty()
+    }
+}
+
+pub fn pane_offset(cols: u32, right: bool) -> u32 {//how far right of the sidebar a pane's grid starts, the right pane sits past the left one and the rule between them
+    match right {
+        true => cols * EST_LENGTH + RULE_WIDTH as u32,
+        false => 0,
+    }
+}
+pub fn in_pane(position: Point, offset: u32, cols: u32) -> bool {//whether position is over the grid area of the pane starting at offset
+    let x = position.x - (SIDEBAR_WIDTH + RULE_WIDTH) as f32 - offset as f32;
+    let y = position.y - (2 * TOP_HEIGHT + RULE_WIDTH) as f32;
+    x >= 0.0 && y >= 0.0 && x < (cols * EST_LENGTH) as f32
+}
@@ -0,0 +1,231 @@
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt, lchown};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use crate::fs_helpers::FileOpError;
+use crate::job_helpers::JobProgress;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PropField {//enum representing the text fields of the properties panel
+    Mode,
+    Owner,
+    Group,
+    Modified,
+    Accessed,
+}
+#[derive(Clone)]
+pub struct Properties {//struct representation of the properties panel, the *_text fields are what the user typed
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+    pub entries: Option<usize>,
+    pub link_target: Option<String>,
+    pub mime: String,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub modified: SystemTime,
+    pub accessed: SystemTime,
+    pub created: Option<SystemTime>,
+    pub mode_text: String,
+    pub owner_text: String,
+    pub group_text: String,
+    pub modified_text: String,
+    pub accessed_text: String,
+    pub recursive: bool,
+}
+
+fn lookup_name(file: &str, id: u32) -> Option<String> {//find the name for an id in /etc/passwd or /etc/group
+    let contents = fs::read_to_string(file).ok()?;
+    contents.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields.get(2).and_then(|x| x.parse::<u32>().ok()) {
+            Some(x) if x == id => Some(fields[0].to_string()),
+            _ => None,
+        }
+    })
+}
+fn lookup_id(file: &str, name: &str) -> Option<u32> {//find the id for a name in /etc/passwd or /etc/group, plain numbers are taken as they are
+    if let Ok(x) = name.trim().parse::<u32>() {
+        return Some(x);
+    }
+    let contents = fs::read_to_string(file).ok()?;
+    contents.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields[0] == name.trim() {
+            true => fields.get(2).and_then(|x| x.parse::<u32>().ok()),
+            false => None,
+        }
+    })
+}
+pub fn user_name(uid: u32) -> String {
+    lookup_name("/etc/passwd", uid).unwrap_or(uid.to_string())
+}
+pub fn group_name(gid: u32) -> String {
+    lookup_name("/etc/group", gid).unwrap_or(gid.to_string())
+}
+pub fn format_time(time: SystemTime) -> String {
+    let local: DateTime<Local> = time.into();
+    local.format(TIME_FORMAT).to_string()
+}
+fn parse_time(text: &str) -> Option<SystemTime> {//read a local time typed in the same format we show it in
+    let naive = NaiveDateTime::parse_from_str(text.trim(), TIME_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).single().map(SystemTime::from)
+}
+pub fn mode_string(mode: u32) -> String {//ls-style rwxr-xr-x
+    let mut output = String::new();
+    for shift in [6, 3, 0] {
+        output.push(if mode >> shift & 0o4 != 0 { 'r' } else { '-' });
+        output.push(if mode >> shift & 0o2 != 0 { 'w' } else { '-' });
+        output.push(if mode >> shift & 0o1 != 0 { 'x' } else { '-' });
+    }
+    output
+}
+fn walk(path: &Path, recursive: bool, progress: Option<&JobProgress>, errors: &mut Vec<FileOpError>, action: &mut dyn FnMut(&Path) -> Result<(), FileOpError>) {//run action on everything below path if asked to and then on path itself, without following symlinks, carrying on past failures
+    if matches!(errors.last(), Some(FileOpError::Cancelled)) {
+        return;
+    }
+    if let Some(x) = progress {
+        if let Err(e) = x.checkpoint() {
+            return errors.push(e);
+        }
+        x.set_current(path);
+    }
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(x) => x,
+        Err(e) => return errors.push(FileOpError::Io(path.to_path_buf(), e)),
+    };
+    if recursive && metadata.is_dir() {//children first, so taking x off a directory can't lock us out of it halfway through
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries {
+                    match entry {
+                        Ok(x) => walk(&x.path(), recursive, progress, errors, action),
+                        Err(e) => errors.push(FileOpError::Io(path.to_path_buf(), e)),
+                    }
+                }
+            }
+            Err(e) => errors.push(FileOpError::Io(path.to_path_buf(), e)),
+        }
+    }
+    if let Err(e) = action(path) {
+        errors.push(e);
+    }
+}
+fn is_file_or_dir(path: &Path) -> bool {//without following symlinks, anything else can't be opened safely
+    fs::symlink_metadata(path).map(|x| x.is_file() || x.is_dir()).unwrap_or(false)
+}
+
+impl Properties {
+    pub fn load(path: &Path, mime: String) -> Result<Properties, FileOpError> {//collect everything the panel shows, without following a symlink
+        let metadata = fs::symlink_metadata(path).map_err(|e| FileOpError::Io(path.to_path_buf(), e))?;
+        let link_target = match metadata.is_symlink() {
+            true => fs::read_link(path).ok().map(|x| x.to_string_lossy().to_string()),
+            false => None,
+        };
+        let entries = match metadata.is_dir() {
+            true => fs::read_dir(path).ok().map(|x| x.count()),
+            false => None,
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let accessed = metadata.accessed().unwrap_or(SystemTime::UNIX_EPOCH);
+        let mode = metadata.mode() & 0o7777;
+        Ok(Properties {
+            path: path.to_path_buf(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            entries,
+            link_target,
+            mime: mime.trim().to_string(),
+            mode,
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            modified,
+            accessed,
+            created: metadata.created().ok(),
+            mode_text: format!("{:o}", mode),
+            owner_text: user_name(metadata.uid()),
+            group_text: group_name(metadata.gid()),
+            modified_text: format_time(modified),
+            accessed_text: format_time(accessed),
+            recursive: false,
+        })
+    }
+    pub fn set_field(&mut self, field: PropField, value: String) {
+        match field {
+            PropField::Mode => self.mode_text = value,
+            PropField::Owner => self.owner_text = value,
+            PropField::Group => self.group_text = value,
+            PropField::Modified => self.modified_text = value,
+            PropField::Accessed => self.accessed_text = value,
+        }
+    }
+    pub fn typed_mode(&self) -> Option<u32> {//the octal entry, if it makes sense
+        u32::from_str_radix(self.mode_text.trim(), 8).ok().filter(|x| *x <= 0o7777)
+    }
+    pub fn toggle_bit(&mut self, bit: u32, on: bool) {//a permission checkbox was flipped, keep the octal entry in step
+        let mode = self.typed_mode().unwrap_or(self.mode);
+        let mode = match on {
+            true => mode | bit,
+            false => mode & !bit,
+        };
+        self.mode_text = format!("{:o}", mode);
+    }
+    pub fn apply(&self, progress: Option<&JobProgress>) -> Vec<FileOpError> {//write back whatever was changed in the panel, carrying on past failures, recursive pushes only those changes down and leaves the rest of each child alone
+        let mut errors = vec![];
+        let recursive = self.recursive && self.is_dir;
+        match self.typed_mode() {
+            Some(mode) if mode != self.mode => {
+                let changed = mode ^ self.mode;
+                walk(&self.path, recursive, progress, &mut errors, &mut |path| {
+                    let metadata = fs::symlink_metadata(path).map_err(|e| FileOpError::Io(path.to_path_buf(), e))?;
+                    if metadata.is_symlink() {//chmod would follow the link, and links have no permissions of their own
+                        return Ok(());
+                    }
+                    let mask = match metadata.is_dir() || path == self.path {
+                        true => changed,
+                        false => changed & 0o666,//a folder's x and setgid bits mean something else on a file, so files below only take read and write
+                    };
+                    let current = metadata.mode() & 0o7777;
+                    let new = (current & !mask) | (mode & mask);
+                    match new == current {
+                        true => Ok(()),
+                        false => fs::set_permissions(path, fs::Permissions::from_mode(new)).map_err(|e| FileOpError::Io(path.to_path_buf(), e)),
+                    }
+                });
+            }
+            Some(..) => {}
+            None => errors.push(FileOpError::InvalidValue(self.mode_text.clone(), String::from("mode"))),
+        }
+        let uid = lookup_id("/etc/passwd", &self.owner_text);
+        let gid = lookup_id("/etc/group", &self.group_text);
+        match (uid, gid) {
+            (Some(uid), Some(gid)) if uid != self.uid || gid != self.gid => {
+                let owner = if uid != self.uid { Some(uid) } else { None };
+                let group = if gid != self.gid { Some(gid) } else { None };
+                walk(&self.path, recursive, progress, &mut errors, &mut |path| lchown(path, owner, group).map_err(|e| FileOpError::Io(path.to_path_buf(), e)));
+            }
+            (Some(..), Some(..)) => {}
+            (None, _) => errors.push(FileOpError::InvalidValue(self.owner_text.clone(), String::from("owner"))),
+            (_, None) => errors.push(FileOpError::InvalidValue(self.group_text.clone(), String::from("group"))),
+        }
+        match (parse_time(&self.modified_text), parse_time(&self.accessed_text)) {
+            (Some(modified), Some(accessed)) if self.modified_text != format_time(self.modified) || self.accessed_text != format_time(self.accessed) => {
+                let times = fs::FileTimes::new().set_modified(modified).set_accessed(accessed);
+                walk(&self.path, recursive, progress, &mut errors, &mut |path| {
+                    if !is_file_or_dir(path) {//opening a fifo would block until something writes to it, and links and devices have no times of their own to set
+                        return Ok(());
+                    }
+                    fs::File::open(path).and_then(|x| x.set_times(times)).map_err(|e| FileOpError::Io(path.to_path_buf(), e))
+                });
+            }
+            (Some(..), Some(..)) => {}
+            (None, _) => errors.push(FileOpError::InvalidValue(self.modified_text.clone(), String::from("modified"))),
+            (_, None) => errors.push(FileOpError::InvalidValue(self.accessed_text.clone(), String::from("accessed"))),
+        }
+        errors
+    }
+}
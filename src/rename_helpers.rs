@@ -0,0 +1,370 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use regex::Regex;
+use crate::config_helpers::get_cache_home;
+use crate::fs_helpers::{FileOpError, move_into_place, split_name, unique_name, validate_name};
+use crate::journal_helpers::JournalOp;
+
+const DEFAULT_DATE: &str = "%Y-%m-%d";
+const TERMINAL_EDITORS: [&str; 10] = ["vi", "vim", "nvim", "nano", "pico", "micro", "hx", "helix", "kak", "joe"];
+const TERMINALS: [&str; 2] = ["x-terminal-emulator", "xterm"];//both take -e and wait for the command to exit
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CaseMode {//enum representing the case transform applied to a name
+    Keep,
+    Lower,
+    Upper,
+    Title,
+}
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BatchField {//enum representing the text fields of the batch rename dialog
+    Find,
+    Replace,
+    Template,
+    Start,
+    Padding,
+}
+#[derive(Clone)]
+pub struct BatchRename {//struct representation of the batch rename dialog
+    pub paths: Vec<PathBuf>,
+    pub find: String,
+    pub replace: String,
+    pub use_regex: bool,
+    pub case: CaseMode,
+    pub template: String,
+    pub start: String,
+    pub padding: String,
+    pub keep_ext: bool,
+}
+pub struct RenamePreview {//what a single file would be called, and why that can't happen if it can't
+    pub source: PathBuf,
+    pub new_name: String,
+    pub problem: Option<String>,
+}
+
+pub fn next_case(case: CaseMode) -> CaseMode {
+    match case {
+        CaseMode::Keep => CaseMode::Lower,
+        CaseMode::Lower => CaseMode::Upper,
+        CaseMode::Upper => CaseMode::Title,
+        CaseMode::Title => CaseMode::Keep,
+    }
+}
+fn apply_case(name: &str, case: CaseMode) -> String {
+    match case {
+        CaseMode::Keep => name.to_string(),
+        CaseMode::Lower => name.to_lowercase(),
+        CaseMode::Upper => name.to_uppercase(),
+        CaseMode::Title => {//capitalize the first letter of every word, words being split by anything that isn't alphanumeric
+            let mut output = String::new();
+            let mut new_word = true;
+            for character in name.chars() {
+                match new_word {
+                    true => output.extend(character.to_uppercase()),
+                    false => output.extend(character.to_lowercase()),
+                }
+                new_word = !character.is_alphanumeric();
+            }
+            output
+        }
+    }
+}
+fn format_date(date: &DateTime<Local>, format: &str) -> Result<String, String> {//strftime, refusing formats chrono would panic on
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|x| matches!(x, Item::Error)) {
+        return Err(format!("\"{}\" is not a valid date format", format));
+    }
+    Ok(date.format_with_items(items.into_iter()).to_string())
+}
+fn expand_template(template: &str, name: &str, ext: &str, counter: &str, modified: &DateTime<Local>) -> Result<String, String> {//fill in {name}, {ext}, {n} and {date} or {date:format}, leaving anything else alone
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        output.push_str(&rest[..open]);
+        let close = match rest[open..].find('}') {
+            Some(x) => open + x,
+            None => {
+                rest = &rest[open..];
+                break;
+            }
+        };
+        let token = &rest[open + 1..close];
+        match token {
+            "name" => output.push_str(name),
+            "ext" => output.push_str(ext),
+            "n" => output.push_str(counter),
+            "date" => output.push_str(&format_date(modified, DEFAULT_DATE)?),
+            x if x.starts_with("date:") => output.push_str(&format_date(modified, &x[5..])?),
+            _ => output.push_str(&rest[open..=close]),
+        }
+        rest = &rest[close + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+impl BatchRename {
+    pub fn new(paths: Vec<PathBuf>) -> BatchRename {
+        BatchRename {
+            paths,
+            find: String::new(),
+            replace: String::new(),
+            use_regex: false,
+            case: CaseMode::Keep,
+            template: String::from("{name}"),
+            start: String::from("1"),
+            padding: String::from("1"),
+            keep_ext: true,
+        }
+    }
+    pub fn set_field(&mut self, field: BatchField, value: String) {
+        match field {
+            BatchField::Find => self.find = value,
+            BatchField::Replace => self.replace = value,
+            BatchField::Template => self.template = value,
+            BatchField::Start => self.start = value,
+            BatchField::Padding => self.padding = value,
+        }
+    }
+    fn new_name(&self, path: &Path, index: usize, pattern: &Option<Regex>, start: u64, padding: usize) -> Result<String, String> {//run a single name through find/replace, case and template, in that order
+        let full = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        let (stem, dotted_ext) = match self.keep_ext {
+            true => split_name(&full),
+            false => (full.as_str(), ""),
+        };
+        let replaced = match (pattern, self.find.is_empty()) {
+            (_, true) => stem.to_string(),
+            (Some(x), false) => x.replace_all(stem, self.replace.as_str()).to_string(),
+            (None, false) => stem.replace(&self.find, &self.replace),
+        };
+        let cased = apply_case(&replaced, self.case);
+        let counter = format!("{:0width$}", start + index as u64, width = padding);
+        let modified: DateTime<Local> = match fs::symlink_metadata(path).and_then(|x| x.modified()) {
+            Ok(x) => x.into(),
+            Err(..) => Local::now(),
+        };
+        let ext = split_name(&full).1.trim_start_matches('.').to_string();
+        let templated = expand_template(&self.template, &cased, &ext, &counter, &modified)?;
+        Ok(format!("{}{}", templated, dotted_ext))
+    }
+    pub fn preview(&self) -> Result<Vec<RenamePreview>, String> {//work out every new name and flag the ones that can't be applied, errors are for the dialog's settings as a whole
+        let pattern = match self.use_regex && !self.find.is_empty() {
+            true => Some(Regex::new(&self.find).map_err(|e| e.to_string())?),
+            false => None,
+        };
+        let start = self.start.trim().parse::<u64>().map_err(|_| format!("\"{}\" is not a valid start number", self.start))?;
+        let padding = self.padding.trim().parse::<usize>().map_err(|_| format!("\"{}\" is not a valid padding", self.padding))?;
+        let mut previews = vec![];
+        for (i, path) in self.paths.iter().enumerate() {
+            let new_name = self.new_name(path, i, &pattern, start, padding)?;
+            previews.push(RenamePreview { source: path.clone(), new_name, problem: None });
+        }
+        let sources: HashSet<&PathBuf> = self.paths.iter().collect();
+        let mut targets: HashMap<PathBuf, usize> = HashMap::new();
+        for preview in &previews {
+            if let Some(parent) = preview.source.parent() {
+                *targets.entry(parent.join(&preview.new_name)).or_insert(0) += 1;
+            }
+        }
+        for preview in &mut previews {
+            let target = preview.source.with_file_name(&preview.new_name);
+            preview.problem = if let Err(e) = validate_name(&preview.new_name) {
+                Some(e.to_string())
+            } else if targets.get(&target).copied().unwrap_or(0) > 1 {
+                Some(String::from("more than one file would get this name"))
+            } else if target != preview.source && !sources.contains(&target) && fs::symlink_metadata(&target).is_ok() {//names freed up by the batch itself are fair game
+                Some(format!("{} already exists", target.display()))
+            } else {
+                None
+            };
+        }
+        Ok(previews)
+    }
+}
+
+pub fn apply_renames(renames: Vec<(PathBuf, PathBuf)>) -> (Vec<JournalOp>, Vec<FileOpError>) {//rename everything by way of temporary names, so swaps and chains can't trip over each other
+    let mut ops = vec![];
+    let mut errors = vec![];
+    let mut parked = vec![];
+    for (source, dest) in renames {
+        if source == dest {
+            continue;
+        }
+        let parent = source.parent().unwrap_or(Path::new("/")).to_path_buf();
+        let name = source.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        let temp = parent.join(unique_name(&parent, &format!(".narwhal-rename-{}", name)));
+        match move_into_place(&source, &temp) {
+            Ok(..) => {
+                ops.push(JournalOp::Move { from: source.to_string_lossy().to_string(), to: temp.to_string_lossy().to_string() });
+                parked.push((source, temp, dest));
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    for (source, temp, dest) in parked {
+        match move_into_place(&temp, &dest) {
+            Ok(..) => ops.push(JournalOp::Move { from: temp.to_string_lossy().to_string(), to: dest.to_string_lossy().to_string() }),
+            Err(e) => {//put it back where it came from rather than leaving a temporary name behind
+                errors.push(e);
+                match move_into_place(&temp, &source) {
+                    Ok(..) => ops.push(JournalOp::Move { from: temp.to_string_lossy().to_string(), to: source.to_string_lossy().to_string() }),
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+    }
+    (ops, errors)
+}
+
+fn edit_list_home() -> PathBuf {
+    PathBuf::from(format!("{}/NarwhalFM-rename.txt", get_cache_home()))
+}
+pub fn write_edit_list(paths: &[PathBuf]) -> Result<PathBuf, FileOpError> {//dump the names into a numbered list for an editor, the numbers tie each line back to its file
+    let list = edit_list_home();
+    let mut contents = String::new();
+    for (i, path) in paths.iter().enumerate() {
+        let name = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        contents.push_str(&format!("{}\t{}\n", i + 1, name));
+    }
+    fs::write(&list, contents).map_err(|e| FileOpError::Io(list.clone(), e))?;
+    Ok(list)
+}
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    env::var_os("PATH").and_then(|x| env::split_paths(&x).map(|dir| dir.join(program)).find(|x| x.is_file()))
+}
+pub fn editor_command() -> Result<(String, Vec<String>), String> {//$VISUAL, then $EDITOR, then vi, split so that things like "code --wait" work
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).unwrap_or_default();
+    let mut words: Vec<String> = editor.split_whitespace().map(|x| x.to_string()).collect();
+    if words.is_empty() {
+        words.push(String::from("vi"));
+    }
+    let name = Path::new(&words[0]).file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+    if !TERMINAL_EDITORS.contains(&name.as_str()) || io::stdin().is_terminal() {
+        let program = words.remove(0);
+        return Ok((program, words));
+    }
+    let terminal = env::var("TERMINAL").ok().filter(|x| !x.is_empty()).or_else(|| TERMINALS.iter().find(|x| find_in_path(x).is_some()).map(|x| x.to_string()));
+    match terminal {//launched from the desktop there is no terminal for the editor to draw in, so open one
+        Some(x) => Ok((x, [String::from("-e")].into_iter().chain(words).collect())),
+        None => Err(format!("{} needs a terminal to run in, set $VISUAL to a graphical editor or $TERMINAL to a terminal emulator", name)),
+    }
+}
+pub fn run_editor(program: String, args: Vec<String>, list: PathBuf) -> Result<(), String> {//blocks until the editor exits, so keep it off the UI thread
+    match Command::new(&program).args(args).arg(list).status() {
+        Ok(x) if x.success() => Ok(()),
+        Ok(x) => Err(format!("{} exited with {}", program, x)),
+        Err(e) => Err(format!("{}: {}", program, e)),
+    }
+}
+pub fn read_edit_list(paths: &[PathBuf]) -> Result<(Vec<(PathBuf, PathBuf)>, Vec<PathBuf>), String> {//read the edited list back as renames plus removed lines
+    let list = edit_list_home();
+    let contents = fs::read_to_string(&list).map_err(|e| format!("{}: {}", list.display(), e))?;
+    let _ = fs::remove_file(&list);
+    parse_edit_list(&contents, paths)
+}
+fn parse_edit_list(contents: &str, paths: &[PathBuf]) -> Result<(Vec<(PathBuf, PathBuf)>, Vec<PathBuf>), String> {//refuse anything ambiguous rather than guess which file a line meant
+    if contents.trim().is_empty() {//almost certainly an aborted edit rather than a request to trash everything
+        return Err(String::from("the list came back empty, nothing was changed"));
+    }
+    let mut new_names: Vec<Option<String>> = vec![None; paths.len()];
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (number, name) = match line.split_once('\t') {
+            Some(x) => x,
+            None => return Err(format!("line {} has no number in front of it", line_number + 1)),
+        };
+        let index = match number.trim().parse::<usize>() {
+            Ok(x) if x >= 1 && x <= paths.len() => x - 1,
+            _ => return Err(format!("line {} starts with an unknown number", line_number + 1)),
+        };
+        if new_names[index].is_some() {
+            return Err(format!("number {} shows up more than once", index + 1));
+        }
+        validate_name(name).map_err(|e| e.to_string())?;
+        new_names[index] = Some(name.to_string());
+    }
+    let sources: HashSet<&PathBuf> = paths.iter().collect();
+    let mut targets = HashSet::new();
+    let mut renames = vec![];
+    let mut removed = vec![];
+    for (path, new_name) in paths.iter().zip(new_names) {
+        let name = match new_name {
+            Some(x) => x,
+            None => {
+                removed.push(path.clone());
+                continue;
+            }
+        };
+        let target = path.with_file_name(&name);
+        if !targets.insert(target.clone()) {
+            return Err(format!("more than one file would be called {}", name));
+        }
+        if target != *path && !sources.contains(&target) && fs::symlink_metadata(&target).is_ok() {//removed lines are trashed first, so their names are free too
+            return Err(format!("{} already exists", target.display()));
+        }
+        renames.push((path.clone(), target));
+    }
+    Ok((renames, removed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {//a fresh folder of files named after their own contents
+        let dir = env::temp_dir().join(format!("narwhal-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["a", "b", "c"] {
+            fs::write(dir.join(file), file).unwrap();
+        }
+        dir
+    }
+    fn contents(dir: &Path, file: &str) -> String {
+        fs::read_to_string(dir.join(file)).unwrap()
+    }
+
+    #[test]
+    fn apply_renames_swaps() {
+        let dir = scratch_dir("swap");
+        let (_, errors) = apply_renames(vec![(dir.join("a"), dir.join("b")), (dir.join("b"), dir.join("a"))]);
+        assert!(errors.is_empty());
+        assert_eq!(contents(&dir, "a"), "b");
+        assert_eq!(contents(&dir, "b"), "a");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);//no temporary names left behind
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn apply_renames_cycles() {
+        let dir = scratch_dir("cycle");
+        let (_, errors) = apply_renames(vec![(dir.join("a"), dir.join("b")), (dir.join("b"), dir.join("c")), (dir.join("c"), dir.join("a"))]);
+        assert!(errors.is_empty());
+        assert_eq!(contents(&dir, "a"), "c");
+        assert_eq!(contents(&dir, "b"), "a");
+        assert_eq!(contents(&dir, "c"), "b");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn edit_list_deleted_line() {
+        let dir = env::temp_dir().join("narwhal-test-missing");
+        let paths = vec![dir.join("x"), dir.join("y"), dir.join("z")];
+        let (renames, removed) = parse_edit_list("1\tx\n3\tzz\n", &paths).unwrap();
+        assert_eq!(renames, vec![(dir.join("x"), dir.join("x")), (dir.join("z"), dir.join("zz"))]);
+        assert_eq!(removed, vec![dir.join("y")]);
+    }
+    #[test]
+    fn edit_list_duplicate_number() {
+        let dir = env::temp_dir().join("narwhal-test-missing");
+        let paths = vec![dir.join("x"), dir.join("y")];
+        assert!(parse_edit_list("1\tx\n1\tw\n2\ty\n", &paths).is_err());
+    }
+}
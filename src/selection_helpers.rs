@@ -0,0 +1,118 @@
+use std::collections::BTreeSet;
+
+#[derive(Clone, Default)]
+pub struct Selection {//struct representation of the selected files, every index points into Narwhal::files
+    pub cursor: Option<usize>,
+    anchor: Option<usize>,
+    items: BTreeSet<usize>,
+}
+
+impl Selection {
+    pub fn clear(&mut self) {
+        self.cursor = None;
+        self.anchor = None;
+        self.items.clear();
+    }
+    pub fn select_only(&mut self, index: usize) {//plain click, the entry becomes the whole selection
+        self.cursor = Some(index);
+        self.anchor = Some(index);
+        self.items.clear();
+        self.items.insert(index);
+    }
+    pub fn toggle(&mut self, index: usize) {//ctrl+click, add or remove a single entry
+        if !self.items.remove(&index) {
+            self.items.insert(index);
+        }
+        self.cursor = Some(index);
+        self.anchor = Some(index);
+    }
+    pub fn extend_to(&mut self, index: usize, order: &[usize]) {//shift+click, select everything between the anchor and index in display order
+        let anchor = self.anchor.unwrap_or(index);
+        let start = order.iter().position(|x| *x == anchor);
+        let end = order.iter().position(|x| *x == index);
+        self.items.clear();
+        match (start, end) {
+            (Some(a), Some(b)) => {
+                let (low, high) = if a <= b { (a, b) } else { (b, a) };
+                self.items.extend(order[low..=high].iter().copied());
+            }
+            _ => {
+                self.items.insert(index);
+            }
+        }
+        self.anchor = Some(anchor);
+        self.cursor = Some(index);
+    }
+    pub fn select_all(&mut self, order: &[usize]) {
+        self.items = order.iter().copied().collect();
+        if self.cursor.is_none() {
+            self.cursor = order.first().copied();
+        }
+        self.anchor = self.cursor;
+    }
+    pub fn invert(&mut self, order: &[usize]) {//flip every visible entry
+        self.items = order.iter().copied().filter(|x| !self.items.contains(x)).collect();
+        self.cursor = self.items.iter().next().copied();
+        self.anchor = self.cursor;
+    }
+    pub fn restore(&mut self, items: Vec<usize>, cursor: Option<usize>) {//put back a selection that was kept as paths, once they are indexes again
+        self.items = items.into_iter().collect();
+        self.cursor = cursor;
+        self.anchor = cursor;
+    }
+    pub fn retain(&mut self, visible: &[usize]) {//drop everything that was filtered out of view, so nothing acts on files the user can't see
+        self.items.retain(|x| visible.contains(x));
+        if self.cursor.map(|x| !visible.contains(&x)).unwrap_or(false) {
+            self.cursor = None;
+        }
+        if self.anchor.map(|x| !visible.contains(&x)).unwrap_or(false) {
+            self.anchor = self.cursor;
+        }
+    }
+    pub fn contains(&self, index: usize) -> bool {
+        self.items.contains(&index)
+    }
+    pub fn indexes(&self) -> Vec<usize> {//selected indexes, falling back on the cursor so single-file habits still work
+        if self.items.is_empty() {
+            self.cursor.into_iter().collect()
+        } else {
+            self.items.iter().copied().collect()
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.indexes().len()
+    }
+}
+
+#[derive(Clone)]
+pub struct FileClipboard {//struct representation of cut or copied files, kept until pasted or replaced
+    pub paths: Vec<String>,
+    pub cut: bool,
+}
+
+impl FileClipboard {
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.iter().any(|x| x == path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hiding_drops_hidden_selection() {//entry 0 is the dotfile, 1 and 2 stay visible
+        let mut selection = Selection::default();
+        selection.select_only(0);
+        selection.retain(&[1, 2]);
+        assert!(selection.indexes().is_empty());
+        assert_eq!(selection.cursor, None);
+    }
+    #[test]
+    fn hiding_keeps_visible_selection() {
+        let mut selection = Selection::default();
+        selection.select_all(&[0, 1, 2]);
+        selection.retain(&[1, 2]);
+        assert_eq!(selection.indexes(), vec![1, 2]);
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const CACHE_TTL: Duration = Duration::from_secs(30);//the top folder's mtime misses changes further down, so counts also go stale with age
+
+pub type SizeCache = HashMap<PathBuf, (SystemTime, Instant, DirSize)>;
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct DirSize {//struct representation of everything below a directory
+    pub bytes: u64,
+    pub files: u64,
+    pub dirs: u64,
+}
+#[derive(Default)]
+struct ScanProgress {//counters shared between the walker thread and the UI
+    bytes: AtomicU64,
+    files: AtomicU64,
+    dirs: AtomicU64,
+    cancelled: AtomicBool,
+    done: AtomicBool,
+    finished: Mutex<Vec<(PathBuf, (SystemTime, Instant, DirSize))>>,
+}
+pub struct SizeScan {//a running size count for a set of directories, partial totals can be read at any time
+    pub paths: Vec<PathBuf>,
+    cached: DirSize,
+    progress: Arc<ScanProgress>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::symlink_metadata(path).and_then(|x| x.modified()).ok()
+}
+fn walk(path: &Path, progress: &ScanProgress) -> Option<DirSize> {//count everything below path without following symlinks, None once cancelled
+    let mut size = DirSize::default();
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        if progress.cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let read_output = match fs::read_dir(&dir) {
+            Ok(x) => x,
+            Err(..) => continue,//unreadable directories count as empty
+        };
+        for entry in read_output.flatten() {
+            let metadata = match entry.metadata() {
+                Ok(x) => x,
+                Err(..) => continue,
+            };
+            if metadata.is_dir() {
+                size.dirs += 1;
+                progress.dirs.fetch_add(1, Ordering::Relaxed);
+                stack.push(entry.path());
+            } else {
+                size.files += 1;
+                size.bytes += metadata.len();
+                progress.files.fetch_add(1, Ordering::Relaxed);
+                progress.bytes.fetch_add(metadata.len(), Ordering::Relaxed);
+            }
+        }
+    }
+    Some(size)
+}
+
+impl SizeScan {
+    pub fn start(paths: Vec<PathBuf>, cache: &SizeCache) -> SizeScan {//answer from the cache where the mtime still matches and the count is recent, walk the rest on a thread
+        let mut cached = DirSize::default();
+        let mut missing = vec![];
+        for path in &paths {
+            match (cache.get(path), modified(path)) {
+                (Some((stamp, counted, size)), Some(now)) if *stamp == now && counted.elapsed() < CACHE_TTL => {
+                    cached.bytes += size.bytes;
+                    cached.files += size.files;
+                    cached.dirs += size.dirs;
+                }
+                _ => missing.push(path.clone()),
+            }
+        }
+        let progress = Arc::new(ScanProgress::default());
+        let thread_progress = progress.clone();
+        thread::spawn(move || {
+            for path in missing {
+                let stamp = match modified(&path) {
+                    Some(x) => x,
+                    None => continue,
+                };
+                let counted = Instant::now();//taken before the walk, anything changing during it makes the entry older rather than newer
+                match walk(&path, &thread_progress) {
+                    Some(size) => {
+                        if let Ok(mut x) = thread_progress.finished.lock() {
+                            x.push((path, (stamp, counted, size)));
+                        }
+                    }
+                    None => return,
+                }
+            }
+            thread_progress.done.store(true, Ordering::Relaxed);
+        });
+        SizeScan { paths, cached, progress }
+    }
+    pub fn total(&self) -> DirSize {//cached totals plus whatever the walker has counted so far
+        DirSize {
+            bytes: self.cached.bytes + self.progress.bytes.load(Ordering::Relaxed),
+            files: self.cached.files + self.progress.files.load(Ordering::Relaxed),
+            dirs: self.cached.dirs + self.progress.dirs.load(Ordering::Relaxed),
+        }
+    }
+    pub fn is_done(&self) -> bool {
+        self.progress.done.load(Ordering::Relaxed)
+    }
+    pub fn take_finished(&self) -> Vec<(PathBuf, (SystemTime, Instant, DirSize))> {//directories counted since the last call, ready for the cache
+        match self.progress.finished.lock() {
+            Ok(mut x) => x.drain(..).collect(),
+            Err(..) => vec![],
+        }
+    }
+}
+impl Drop for SizeScan {
+    fn drop(&mut self) {//a replaced scan stops walking on its own
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use crate::archive_helpers::ArchiveMount;
+use crate::config_helpers::SortType;
+use crate::history_helpers::{History, Location};
+
+#[derive(Clone)]
+pub struct TabState {//everything that belongs to a single tab, the active tab lives in Narwhal's own fields and its copy here goes stale until it is switched away from
+    pub current_path: PathBuf,
+    pub sort_type: SortType,
+    pub show_hidden: bool,
+    pub trash_view: bool,
+    pub archive_stack: Vec<ArchiveMount>,
+    pub selected: Vec<PathBuf>,//kept as paths, indexes into files don't survive the directory changing behind our back
+    pub cursor: Option<PathBuf>,
+    pub history: History,
+}
+
+impl TabState {
+    pub fn new(location: Location, sort_type: SortType, show_hidden: bool) -> TabState {//a fresh tab with nothing selected and no history
+        TabState {
+            current_path: location.path,
+            sort_type,
+            show_hidden,
+            trash_view: location.trash,
+            archive_stack: location.archives,
+            selected: vec![],
+            cursor: None,
+            history: History::default(),
+        }
+    }
+    pub fn location(&self) -> Location {
+        Location { path: self.current_path.clone(), trash: self.trash_view, archives: self.archive_stack.clone() }
+    }
+    pub fn saved_path(&self) -> PathBuf {//where to reopen the tab next launch, archive mirrors don't outlive the session so fall back on the folder holding the archive
+        match self.archive_stack.first() {
+            Some(mount) => mount.archive.parent().map(|x| x.to_path_buf()).unwrap_or(PathBuf::from("/")),
+            None => self.current_path.clone(),
+        }
+    }
+}
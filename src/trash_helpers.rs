@@ -0,0 +1,266 @@
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use crate::config_helpers::get_data_home;
+use crate::fs_helpers::{FileOpError, move_into_place, remove_path, unique_name};
+
+const STICKY_BIT: u32 = 0o1000;
+
+#[derive(Clone)]
+pub struct TrashEntry {//struct representation of one trashed item and its .trashinfo
+    pub name: String,
+    pub original_path: PathBuf,
+    pub deletion_date: String,
+    pub files_path: PathBuf,
+    pub info_path: PathBuf,
+}
+
+pub fn current_uid() -> Result<u32, FileOpError> {//collect our uid without reaching for libc
+    fs::metadata("/proc/self").map(|x| x.uid()).map_err(|e| FileOpError::Io(PathBuf::from("/proc/self"), e))
+}
+pub fn home_trash() -> PathBuf {//$XDG_DATA_HOME/Trash, as described by the freedesktop.org trash spec
+    PathBuf::from(format!("{}/Trash", get_data_home()))
+}
+fn device_of(path: &Path) -> Option<u64> {//device id of a path, or of its closest existing ancestor
+    let mut current = Some(path);
+    while let Some(x) = current {
+        if let Ok(metadata) = fs::symlink_metadata(x) {
+            return Some(metadata.dev());
+        }
+        current = x.parent();
+    }
+    None
+}
+fn mount_point(path: &Path) -> Option<PathBuf> {//walk upwards until the device changes, the last directory before that is the mount point
+    let dev = device_of(path)?;
+    let mut top = path.to_path_buf();
+    while let Some(parent) = top.parent() {
+        match fs::metadata(parent) {
+            Ok(x) if x.dev() == dev => top = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    Some(top)
+}
+fn ensure_trash_dir(trash: &Path) -> io::Result<()> {//create files/ and info/ with private permissions if needed
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true).mode(0o700);
+    builder.create(trash.join("files"))?;
+    builder.create(trash.join("info"))
+}
+fn topdir_trash(topdir: &Path, uid: u32) -> Option<PathBuf> {//pick $topdir/.Trash/$uid if the admin set it up properly, else $topdir/.Trash-$uid
+    let shared = topdir.join(".Trash");
+    if let Ok(metadata) = fs::symlink_metadata(&shared) {
+        if metadata.is_dir() && metadata.permissions().mode() & STICKY_BIT != 0 {
+            let candidate = shared.join(uid.to_string());
+            if ensure_trash_dir(&candidate).is_ok() {
+                return Some(candidate);
+            }
+        }
+    }
+    let candidate = topdir.join(format!(".Trash-{uid}"));
+    match fs::symlink_metadata(&candidate) {
+        Ok(x) if x.is_symlink() || !x.is_dir() => None,
+        _ => ensure_trash_dir(&candidate).ok().map(|_| candidate),
+    }
+}
+fn trash_for(path: &Path) -> Result<(PathBuf, Option<PathBuf>), FileOpError> {//figure out which trash directory a path belongs in, plus the topdir paths are relative to
+    let home = home_trash();
+    if device_of(path) == device_of(&home) {
+        ensure_trash_dir(&home).map_err(|e| FileOpError::Io(home.clone(), e))?;
+        return Ok((home, None));
+    }
+    let uid = current_uid()?;
+    let topdir = mount_point(path).ok_or_else(|| FileOpError::NotFound(path.to_path_buf()))?;
+    match topdir_trash(&topdir, uid) {
+        Some(trash) => Ok((trash, Some(topdir))),
+        None => Err(FileOpError::Io(topdir.clone(), io::Error::new(io::ErrorKind::PermissionDenied, "no usable trash directory on this filesystem"))),
+    }
+}
+pub fn all_trash_dirs() -> Vec<PathBuf> {//every trash directory we can see, home trash first
+    let mut dirs = vec![home_trash()];
+    let uid = match current_uid() {
+        Ok(x) => x,
+        Err(..) => return dirs,
+    };
+    let mounts = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    for line in mounts.lines() {
+        let fields: Vec<&str> = line.split(' ').collect();
+        if fields.len() < 2 {
+            continue;
+        }
+        let topdir = PathBuf::from(fields[1].replace("\\040", " "));
+        for candidate in [topdir.join(".Trash").join(uid.to_string()), topdir.join(format!(".Trash-{uid}"))] {
+            if candidate.join("info").is_dir() && !dirs.contains(&candidate) {
+                dirs.push(candidate);
+            }
+        }
+    }
+    dirs
+}
+pub fn encode_trash_path(path: &Path) -> String {//percent-encode a path the way .trashinfo files expect
+    let mut output = String::new();
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            output.push(byte as char);
+        } else {
+            output.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    output
+}
+pub fn decode_trash_path(encoded: &str) -> String {//undo percent-encoding from a .trashinfo file
+    let bytes = encoded.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            match std::str::from_utf8(&bytes[i+1..i+3]).ok().and_then(|x| u8::from_str_radix(x, 16).ok()) {
+                Some(x) => {
+                    output.push(x);
+                    i += 3;
+                    continue;
+                }
+                None => {}
+            }
+        }
+        output.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&output).to_string()
+}
+fn reserve_info(trash: &Path, name: &str) -> Result<(String, fs::File), FileOpError> {//atomically claim a free name in the trash by creating its .trashinfo
+    let mut counter = 1;
+    loop {
+        let candidate = match counter {
+            1 => name.to_string(),
+            _ => format!("{name}.{counter}"),
+        };
+        let info_path = trash.join("info").join(format!("{candidate}.trashinfo"));
+        if !trash.join("files").join(&candidate).exists() {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&info_path) {
+                Ok(file) => return Ok((candidate, file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(FileOpError::Io(info_path, e)),
+            }
+        }
+        counter += 1;
+    }
+}
+pub fn trash_path(path: &Path) -> Result<TrashEntry, FileOpError> {//move a file or directory into the trash that matches its filesystem
+    fs::symlink_metadata(path).map_err(|e| FileOpError::Io(path.to_path_buf(), e))?;
+    let name = match path.file_name() {
+        Some(x) => x.to_string_lossy().to_string(),
+        None => return Err(FileOpError::InvalidName(path.to_string_lossy().to_string())),
+    };
+    let (trash, topdir) = trash_for(path)?;
+    let (trashed_name, mut info_file) = reserve_info(&trash, &name)?;
+    let info_path = trash.join("info").join(format!("{trashed_name}.trashinfo"));
+    let files_path = trash.join("files").join(&trashed_name);
+    let recorded_path = match &topdir {
+        Some(top) => path.strip_prefix(top).map(|x| x.to_path_buf()).unwrap_or(path.to_path_buf()),
+        None => path.to_path_buf(),
+    };
+    let deletion_date = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let info = format!("[Trash Info]\nPath={}\nDeletionDate={}\n", encode_trash_path(&recorded_path), deletion_date);
+    if let Err(e) = info_file.write_all(info.as_bytes()) {
+        let _ = fs::remove_file(&info_path);
+        return Err(FileOpError::Io(info_path, e));
+    }
+    if let Err(e) = fs::rename(path, &files_path) {
+        let _ = fs::remove_file(&info_path);
+        return Err(FileOpError::Io(path.to_path_buf(), e));
+    }
+    Ok(TrashEntry { name: trashed_name, original_path: path.to_path_buf(), deletion_date, files_path, info_path })
+}
+fn topdir_of(trash: &Path) -> Option<PathBuf> {//the directory relative .trashinfo paths hang off of
+    let name = trash.file_name()?.to_string_lossy().to_string();
+    if trash == home_trash() {
+        None
+    } else if name.starts_with(".Trash-") {
+        trash.parent().map(|x| x.to_path_buf())
+    } else {
+        trash.parent().and_then(|x| x.parent()).map(|x| x.to_path_buf())
+    }
+}
+fn parse_info(text: &str) -> Option<(String, String)> {//pull Path and DeletionDate out of a .trashinfo file
+    let mut path = None;
+    let mut date = String::new();
+    let mut in_section = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line == "[Trash Info]";
+        } else if in_section {
+            if let Some(x) = line.strip_prefix("Path=") {
+                path = Some(decode_trash_path(x));
+            } else if let Some(x) = line.strip_prefix("DeletionDate=") {
+                date = x.to_string();
+            }
+        }
+    }
+    path.map(|x| (x, date))
+}
+pub fn list_trash() -> Vec<TrashEntry> {//collect every valid item from every visible trash directory
+    let mut entries = vec![];
+    for trash in all_trash_dirs() {
+        let topdir = topdir_of(&trash);
+        let read_output = match fs::read_dir(trash.join("info")) {
+            Ok(x) => x,
+            Err(..) => continue,
+        };
+        for info in read_output.flatten() {
+            let info_path = info.path();
+            let name = match info_path.file_name().and_then(|x| x.to_str()).and_then(|x| x.strip_suffix(".trashinfo")) {
+                Some(x) => x.to_string(),
+                None => continue,
+            };
+            let files_path = trash.join("files").join(&name);
+            if fs::symlink_metadata(&files_path).is_err() {//orphaned info file, nothing to show
+                continue;
+            }
+            let (path, deletion_date) = match fs::read_to_string(&info_path).ok().as_deref().and_then(parse_info) {
+                Some(x) => x,
+                None => continue,
+            };
+            let original_path = match &topdir {
+                Some(top) if !path.starts_with('/') => top.join(path),
+                _ => PathBuf::from(path),
+            };
+            entries.push(TrashEntry { name, original_path, deletion_date, files_path, info_path });
+        }
+    }
+    entries
+}
+pub fn restore_entry(entry: &TrashEntry) -> Result<PathBuf, FileOpError> {//put a trashed item back where it came from, picking a fresh name if that spot got taken
+    let parent = match entry.original_path.parent() {
+        Some(x) => x.to_path_buf(),
+        None => return Err(FileOpError::InvalidName(entry.original_path.to_string_lossy().to_string())),
+    };
+    fs::create_dir_all(&parent).map_err(|e| FileOpError::Io(parent.clone(), e))?;
+    let name = match entry.original_path.file_name() {
+        Some(x) => x.to_string_lossy().to_string(),
+        None => entry.name.clone(),
+    };
+    let dest = parent.join(unique_name(&parent, &name));
+    move_into_place(&entry.files_path, &dest)?;
+    let _ = fs::remove_file(&entry.info_path);
+    Ok(dest)
+}
+pub fn delete_entry(entry: &TrashEntry) -> Result<(), FileOpError> {//permanently remove a trashed item along with its .trashinfo
+    remove_path(&entry.files_path)?;
+    fs::remove_file(&entry.info_path).map_err(|e| FileOpError::Io(entry.info_path.clone(), e))
+}
+pub fn empty_trash() -> Result<(), FileOpError> {//permanently remove everything in every trash, reporting the first failure
+    let mut first_error = None;
+    for entry in list_trash() {
+        if let Err(e) = delete_entry(&entry) {
+            first_error.get_or_insert(e);
+        }
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
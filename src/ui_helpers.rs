@@ -1,6 +1,7 @@
-use iced::widget::{Column, Button, svg, Text};
+use iced::Color;
+use iced::widget::{Column, Button, MouseArea, mouse_area, Row, svg, Text, text_input, TextInput};
 use iced_style::theme;
-use crate::{Message, clip_file_name, FONT_SIZE, IMAGE_SCALE};
+use crate::{Message, clip_file_name, EST_HEIGHT, EST_LENGTH, FONT_SIZE, IMAGE_SCALE, SPACING};
 
 
 #[derive(Clone)]
@@ -8,21 +9,42 @@ pub struct UIFile {//struct for holding the set of data needed for rendering
     pub name: String,
     pub original_index: usize,
     pub selected: bool,
+    pub dimmed: bool,
+    pub targeted: bool,
     pub icon: String,
 }
 
 impl UIFile {
-    pub async fn render<'a>(&self) -> Column<'a, Message> {//render self into a column
+    pub async fn render<'a>(&self, editing: Option<(String, text_input::Id, Option<String>)>) -> MouseArea<'a, Message> {//render self into a column, with a rename editor in place of the name if editing, pressing it may start a click or a drag, a middle click opens a folder in a new tab
         let file_icon = self.icon.clone();
         let handle = svg::Handle::from_path(file_icon);
         let image = svg(handle).height(IMAGE_SCALE).width(IMAGE_SCALE);
-        let text = Text::new(clip_file_name(self.name.clone())).size(FONT_SIZE);
-        let button = if self.selected {
-            Button::new(image).on_press(Message::FileClicked(self.original_index))
+        let text = if self.dimmed {//cut files are shown faded until they are pasted
+            Text::new(clip_file_name(self.name.clone())).size(FONT_SIZE).style(Color::from_rgba(0.5, 0.5, 0.5, 0.6))
         } else {
-            Button::new(image).on_press(Message::FileClicked(self.original_index)).style(theme::Button::Text)
+            Text::new(clip_file_name(self.name.clone())).size(FONT_SIZE)
         };
-        Column::new().push(button).push(text).align_items(iced::Alignment::Center)
+        let button = if self.targeted {//the mouse area around the tile takes the press, a button with on_press would swallow it
+            Button::new(image).style(theme::Button::Positive)
+        } else if self.dimmed {
+            Button::new(image).style(theme::Button::Secondary)
+        } else if self.selected {
+            Button::new(image)
+        } else {
+            Button::new(image).style(theme::Button::Text)
+        };
+        let tile = match editing {
+            Some((value, id, problem)) => {
+                let input = TextInput::new("", &value).on_input(Message::RenameUpdate).size(FONT_SIZE).width((EST_LENGTH - SPACING as u32) as f32).id(id);
+                let mut tile = Column::new().push(button).push(input).align_items(iced::Alignment::Center).width((EST_LENGTH - SPACING as u32) as f32).height(EST_HEIGHT as f32);
+                if let Some(x) = problem {//say what is wrong with the name while it is being typed
+                    tile = tile.push(Text::new(x).size(FONT_SIZE).width((EST_LENGTH - SPACING as u32) as f32).style(Color::from_rgb(0.8, 0.2, 0.2)));
+                }
+                tile
+            }
+            None => Column::new().push(button).push(text).align_items(iced::Alignment::Center).width((EST_LENGTH - SPACING as u32) as f32).height(EST_HEIGHT as f32),//fixed size tiles, so a position can be mapped back to a tile
+        };
+        mouse_area(tile).on_press(Message::DragStart(self.original_index)).on_middle_press(Message::OpenInNewTab(self.original_index))
     }
 }
 /* 
@@ -32,4 +54,29 @@ pub fn localized_button<'a>(msg_id: &str, fontsize: u16) -> Button<'a, Message>
 */
 pub fn string_button<'a>(msg: String, fontsize: u16)-> Button<'a, Message> {
     Button::new(Text::new(msg).size(fontsize))
-}
\ No newline at end of file
+}
+pub fn grid<'a>(tiles: Vec<MouseArea<'a, Message>>, cols: u32) -> Column<'a, Message> {//lay rendered tiles out in rows of cols
+    let mut listing = Column::new();
+    let mut row = Row::new().spacing(SPACING);
+    for (i, tile) in tiles.into_iter().enumerate() {
+        if i > 0 && i % cols as usize == 0 {
+            listing = listing.push(row);
+            row = Row::new().spacing(SPACING);
+        }
+        row = row.push(tile);
+    }
+    listing.push(row)
+}
+pub fn format_size(bytes: u64) -> String {//human readable size, in powers of 1024
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, units[0]),
+        _ => format!("{:.1} {}", size, units[unit]),
+    }
+}